@@ -0,0 +1,333 @@
+use svg;
+use types::{AgendaFormat, Event, HTMLElement, Options};
+
+const ROW_HEIGHT: u16 = 40;
+const LABEL_HEIGHT: u16 = 24;
+const GROUP_INDENT: u16 = 10;
+
+#[derive(Debug, PartialEq)]
+pub enum AgendaItem<'e> {
+    /// Time range shared by overlapping events
+    Header(u16, u16),
+    /// Event and whether it belongs to overlapping group
+    Event(&'e Event, bool),
+    /// Free time in minutes
+    Gap(u16),
+}
+
+/// Format duration like "1h 30m"
+pub fn format_duration(duration: u16) -> String {
+    let (hours, minutes) = (duration / 60, duration % 60);
+    match (hours, minutes) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+fn push_group<'e>(items: &mut Vec<AgendaItem<'e>>, group: &Vec<&'e Event>) {
+    let grouped = group.len() > 1;
+    if grouped {
+        let starts_at = group[0].starts_at;
+        let ends_at = group.iter().map(|event| event.ends_at()).max().unwrap();
+        items.push(AgendaItem::Header(starts_at, ends_at));
+    }
+    for event in group {
+        items.push(AgendaItem::Event(event, grouped));
+    }
+}
+
+fn push_gap(items: &mut Vec<AgendaItem>, gap: u16, min_gap: u16) {
+    if gap > min_gap {
+        items.push(AgendaItem::Gap(gap));
+    }
+}
+
+/// Chronological list of events, overlapping events are grouped together
+/// and free gaps longer than `min_gap` minutes are listed explicitly,
+/// including ones before the first and after the last event on the grid
+pub fn build<'e>(events: &'e Vec<Event>, min_gap: u16) -> Vec<AgendaItem<'e>> {
+    let mut sorted: Vec<&Event> = events.iter().collect();
+    sorted.sort_by_key(|event| event.starts_at);
+
+    let mut items = vec![];
+    let mut group: Vec<&Event> = vec![];
+    // Start of the grid until the first event
+    let mut group_ends_at = 0;
+
+    for event in sorted {
+        if !group.is_empty() && event.starts_at >= group_ends_at {
+            push_group(&mut items, &group);
+            group.clear();
+        }
+        if group.is_empty() {
+            push_gap(&mut items, event.starts_at - group_ends_at, min_gap);
+        }
+
+        if group.is_empty() || event.ends_at() > group_ends_at {
+            group_ends_at = event.ends_at();
+        }
+        group.push(event);
+    }
+
+    if !group.is_empty() {
+        push_group(&mut items, &group);
+    }
+    push_gap(
+        &mut items,
+        svg::grid_duration().saturating_sub(group_ends_at),
+        min_gap,
+    );
+
+    items
+}
+
+/// Overlapping events are listed under a shared header
+fn create_group<'a>(starts_at: u16, ends_at: u16) -> (HTMLElement<'a>, HTMLElement<'a>) {
+    let mut group = HTMLElement::new("li").class("agenda-group");
+    let mut header = HTMLElement::new("div").class("agenda-header");
+    header.append_child(svg::format_time_range(starts_at, ends_at));
+    group.append_child(header);
+
+    (group, HTMLElement::new("ul"))
+}
+
+fn close_group<'a>(list: &mut HTMLElement<'a>, group: Option<(HTMLElement<'a>, HTMLElement<'a>)>) {
+    if let Some((mut group, events)) = group {
+        group.append_child(events);
+        list.append_child(group);
+    }
+}
+
+fn render_html<'a>(items: &Vec<AgendaItem>) -> HTMLElement<'a> {
+    let mut list = HTMLElement::new("ul").class("agenda");
+    let mut group = None;
+
+    for item in items {
+        match item {
+            AgendaItem::Header(starts_at, ends_at) => {
+                close_group(&mut list, group.take());
+                group = Some(create_group(*starts_at, *ends_at));
+            }
+            AgendaItem::Event(event, grouped) => {
+                let mut li = HTMLElement::new("li").class("agenda-event");
+                let mut time = HTMLElement::new("time");
+                time.append_child(svg::format_event_time(event));
+                li.append_child(time);
+                if let Some(label) = svg::event_label(event) {
+                    let mut strong = HTMLElement::new("strong");
+                    strong.append_child(label);
+                    li.append_child(strong);
+                }
+
+                match (grouped, group.as_mut()) {
+                    (true, Some((_, events))) => events.append_child(li),
+                    _ => {
+                        close_group(&mut list, group.take());
+                        list.append_child(li);
+                    }
+                }
+            }
+            AgendaItem::Gap(gap) => {
+                close_group(&mut list, group.take());
+                let mut li = HTMLElement::new("li").class("agenda-gap");
+                li.append_child(format!("{} free", format_duration(*gap)));
+                list.append_child(li);
+            }
+        }
+    }
+    close_group(&mut list, group.take());
+
+    list
+}
+
+fn create_row<'a>(offset: u16, height: u16) -> HTMLElement<'a> {
+    let mut g = HTMLElement::new("g").translate(0, offset);
+    // Keep row height for hit area even if text is shorter
+    g.append_child(
+        HTMLElement::new("rect")
+            .width(svg::SVG_WIDTH)
+            .height(height)
+            .fill("none"),
+    );
+    g
+}
+
+fn create_label<'a>(text: String, fill: &str) -> HTMLElement<'a> {
+    let mut label = HTMLElement::new("text")
+        .dy(".35em")
+        .y(LABEL_HEIGHT / 2)
+        .fill(fill);
+    label.append_child(text);
+    label
+}
+
+fn create_event_row<'a>(event: &Event, grouped: bool, offset: u16) -> HTMLElement<'a> {
+    let mut g = create_row(offset, ROW_HEIGHT);
+    let indent = if grouped { GROUP_INDENT } else { 0 };
+
+    g.append_child(
+        HTMLElement::new("line")
+            .stroke_width(4)
+            .stroke("#45a51c")
+            .x1(indent)
+            .x2(indent)
+            .y1(4)
+            .y2(ROW_HEIGHT - 4),
+    );
+
+    let mut text = HTMLElement::new("text")
+        .translate(indent + 10, ROW_HEIGHT / 2)
+        .fill("#45a51c");
+    let has_title = match svg::event_label(event) {
+        Some(label) => {
+            let mut tspan = HTMLElement::new("tspan")
+                .style("font-weight: bold;")
+                .dy("-0.1em");
+            tspan.append_child(label);
+            text.append_child(tspan);
+            true
+        }
+        None => false,
+    };
+    let dy = if has_title { "0.9em" } else { "0.35em" };
+    let mut time = HTMLElement::new("tspan").dy(dy).x(0);
+    time.append_child(svg::format_event_time(event));
+    text.append_child(time);
+    g.append_child(text);
+
+    g
+}
+
+fn render_svg<'a>(items: &Vec<AgendaItem>) -> HTMLElement<'a> {
+    let mut rows = HTMLElement::new("g");
+    let mut offset = 0;
+
+    for item in items {
+        match item {
+            AgendaItem::Header(starts_at, ends_at) => {
+                let mut row = create_row(offset, LABEL_HEIGHT);
+                let label = create_label(svg::format_time_range(*starts_at, *ends_at), "#919191");
+                row.append_child(label);
+                rows.append_child(row);
+                offset += LABEL_HEIGHT;
+            }
+            AgendaItem::Event(event, grouped) => {
+                rows.append_child(create_event_row(event, *grouped, offset));
+                offset += ROW_HEIGHT;
+            }
+            AgendaItem::Gap(gap) => {
+                let mut row = create_row(offset, LABEL_HEIGHT);
+                let label = create_label(format!("{} free", format_duration(*gap)), "#c9c9c9");
+                row.append_child(label);
+                rows.append_child(row);
+                offset += LABEL_HEIGHT;
+            }
+        }
+    }
+
+    let mut svg = HTMLElement::new("svg")
+        .xmlns(svg::SVG_NS)
+        .width(svg::SVG_WIDTH)
+        .height(offset)
+        .view_box(svg::SVG_WIDTH, offset)
+        .style("font-family: Helvetica, Arial; font-size: 0.8em;");
+    svg.append_child(rows);
+    svg
+}
+
+pub fn render(events: &Vec<Event>, options: &Options) -> String {
+    let items = build(events, options.min_free_gap);
+
+    let el = match options.agenda_format {
+        AgendaFormat::Svg => render_svg(&items),
+        AgendaFormat::Html => render_html(&items),
+    };
+    svg::markup(&el, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: u16, starts_at: u16, duration: u16) -> Event {
+        Event {
+            id: id,
+            starts_at: starts_at,
+            duration: duration,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45m");
+        assert_eq!(format_duration(120), "2h");
+        assert_eq!(format_duration(90), "1h 30m");
+    }
+
+    #[test]
+    fn test_build() {
+        let events = vec![
+            event(0, 120, 60),
+            event(1, 0, 30),
+            event(2, 150, 60),
+            event(3, 30, 30),
+            event(4, 300, 15),
+        ];
+
+        assert_eq!(
+            build(&events, 30),
+            vec![
+                AgendaItem::Event(&events[1], false),
+                AgendaItem::Event(&events[3], false),
+                AgendaItem::Gap(60),
+                AgendaItem::Header(120, 210),
+                AgendaItem::Event(&events[0], true),
+                AgendaItem::Event(&events[2], true),
+                AgendaItem::Gap(90),
+                AgendaItem::Event(&events[4], false),
+                AgendaItem::Gap(405),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_window_gaps() {
+        let events = vec![event(0, 60, 30), event(1, 120, 600)];
+
+        assert_eq!(
+            build(&events, 30),
+            vec![
+                AgendaItem::Gap(60),
+                AgendaItem::Event(&events[0], false),
+                AgendaItem::Event(&events[1], false),
+            ]
+        );
+        // Gap has to be longer than the threshold
+        assert_eq!(build(&events, 60)[0], AgendaItem::Event(&events[0], false));
+        assert_eq!(build(&vec![], 30), vec![AgendaItem::Gap(720)]);
+    }
+
+    #[test]
+    fn test_render_html() {
+        let mut events = vec![event(0, 0, 60), event(1, 30, 45), event(2, 165, 5)];
+        events[0].title = Some("Standup".to_string());
+
+        assert_eq!(
+            render_html(&build(&events, 60)).to_string(),
+            "<ul class=\"agenda\">\
+             <li class=\"agenda-group\">\
+             <div class=\"agenda-header\">9 AM – 10:15 AM</div>\
+             <ul>\
+             <li class=\"agenda-event\"><time>9 AM – 10 AM</time><strong>Standup</strong></li>\
+             <li class=\"agenda-event\"><time>9:30 AM – 10:15 AM</time></li>\
+             </ul>\
+             </li>\
+             <li class=\"agenda-gap\">1h 30m free</li>\
+             <li class=\"agenda-event\"><time>11:45 AM – 11:50 AM</time></li>\
+             <li class=\"agenda-gap\">9h 10m free</li>\
+             </ul>"
+        );
+    }
+}
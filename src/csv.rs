@@ -0,0 +1,395 @@
+use date::{self, Date};
+use events;
+use std::fmt;
+use std::fmt::Display;
+use svg;
+use types::{CsvColumns, Event, ExternalEvent, Options};
+
+/// Problem with the row starting at `line`, lines are counted from 1
+#[derive(Debug, PartialEq)]
+pub struct CsvError {
+    pub line: usize,
+    pub message: String,
+}
+impl CsvError {
+    fn new(line: usize, message: String) -> CsvError {
+        CsvError {
+            line: line,
+            message: message,
+        }
+    }
+}
+impl Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Split RFC 4180 text into rows with line number where each row starts,
+/// quoted fields may contain separators, quotes and line breaks
+pub fn parse_rows(text: &str) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                line += 1;
+                field.push(c);
+            }
+            ',' if !quoted => row.push(field.split_off(0)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(field.split_off(0));
+                rows.push((row_line, row.split_off(0)));
+                line += 1;
+                row_line = line;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(CsvError::new(
+            row_line,
+            "unterminated quoted field".to_string(),
+        ));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+
+    // Blank lines are not rows
+    Ok(rows
+        .into_iter()
+        .filter(|(_, row)| !(row.len() == 1 && row[0].trim().is_empty()))
+        .collect())
+}
+
+/// Minutes since start of the grid from minutes or "HH:MM" time, hours
+/// past 23 are times after midnight as written by `format_time`
+fn parse_time(value: &str) -> Option<u16> {
+    if value.contains(':') {
+        let minutes = match date::parse_time(value) {
+            Some(minutes) => minutes,
+            None => {
+                let idx = value.find(':')?;
+                let hour: u16 = value[..idx].parse().ok().filter(|hour| *hour >= 24)?;
+                let minutes = date::parse_time(&format!("{}{}", hour % 24, &value[idx..]))?;
+                (hour / 24).checked_mul(24 * 60)?.checked_add(minutes)?
+            }
+        };
+        minutes.checked_sub(svg::FIRST_HOUR * 60)
+    } else {
+        value.parse().ok()
+    }
+}
+
+fn format_time(minutes: u16) -> String {
+    let minutes = svg::FIRST_HOUR as u32 * 60 + minutes as u32;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+struct ColumnIndexes {
+    title: Option<usize>,
+    location: Option<usize>,
+    start: usize,
+    end: Option<usize>,
+    duration: Option<usize>,
+    date: Option<usize>,
+}
+
+fn find_columns(header: &Vec<String>, columns: &CsvColumns) -> Result<ColumnIndexes, String> {
+    let find = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+    let find_optional = |name: &Option<String>| name.as_ref().and_then(|name| find(name));
+
+    let indexes = ColumnIndexes {
+        title: find(&columns.title),
+        location: find(&columns.location),
+        start: find(&columns.start)
+            .ok_or_else(|| format!("missing column \"{}\"", columns.start))?,
+        end: find_optional(&columns.end),
+        duration: find_optional(&columns.duration),
+        date: find_optional(&columns.date),
+    };
+    if indexes.end.is_none() && indexes.duration.is_none() {
+        return Err("missing end or duration column".to_string());
+    }
+
+    Ok(indexes)
+}
+
+fn read_row(row: &Vec<String>, indexes: &ColumnIndexes) -> Result<ExternalEvent, String> {
+    let cell = |idx: Option<usize>| {
+        idx.and_then(|idx| row.get(idx))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    let start = cell(Some(indexes.start)).ok_or("missing start")?;
+    let starts_at = parse_time(start).ok_or_else(|| format!("invalid start \"{}\"", start))?;
+    let duration = match (cell(indexes.end), cell(indexes.duration)) {
+        (Some(end), _) => {
+            let ends_at = parse_time(end).ok_or_else(|| format!("invalid end \"{}\"", end))?;
+            if ends_at <= starts_at {
+                return Err(format!("end \"{}\" is not after start \"{}\"", end, start));
+            }
+            ends_at - starts_at
+        }
+        (None, Some(duration)) => duration
+            .parse()
+            .map_err(|_| format!("invalid duration \"{}\"", duration))?,
+        (None, None) => return Err("missing end or duration".to_string()),
+    };
+    let date = match cell(indexes.date) {
+        Some(date) => {
+            Date::parse(date).ok_or_else(|| format!("invalid date \"{}\"", date))?;
+            Some(date.to_string())
+        }
+        None => None,
+    };
+
+    Ok(ExternalEvent {
+        starts_at: starts_at,
+        duration: duration,
+        title: cell(indexes.title).map(|title| title.to_string()),
+        location: cell(indexes.location).map(|location| location.to_string()),
+        date: date,
+        ..Default::default()
+    })
+}
+
+/// Read events from CSV with header, errors of all rows are collected
+pub fn read(text: &str, columns: &CsvColumns) -> Result<Vec<ExternalEvent>, Vec<CsvError>> {
+    let rows = parse_rows(text).map_err(|error| vec![error])?;
+    let (header_line, header) = match rows.first() {
+        Some(&(line, ref header)) => (line, header),
+        None => return Ok(vec![]),
+    };
+    let indexes = find_columns(header, columns)
+        .map_err(|message| vec![CsvError::new(header_line, message)])?;
+
+    let mut raw_events = vec![];
+    let mut errors = vec![];
+    for &(line, ref row) in &rows[1..] {
+        match read_row(row, &indexes) {
+            Ok(raw_event) => raw_events.push(raw_event),
+            Err(message) => errors.push(CsvError::new(line, message)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(raw_events)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Same events as `events::deserialize` produces for JSON
+pub fn deserialize(text: &str, options: &Options) -> Result<Vec<Event>, Vec<CsvError>> {
+    let raw_events = read(text, &options.csv_columns)?;
+    Ok(events::from_external(raw_events, options))
+}
+
+fn escape_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write events as CSV readable by `read` with the same columns
+pub fn write(events: &Vec<Event>, columns: &CsvColumns) -> String {
+    let mut header = vec![
+        columns.title.clone(),
+        columns.location.clone(),
+        columns.start.clone(),
+    ];
+    let use_end = columns.end.is_some();
+    header.extend(columns.end.clone().or(columns.duration.clone()));
+    header.extend(columns.date.clone());
+
+    let mut csv = String::new();
+    let mut write_row = |row: Vec<String>| {
+        let row: Vec<String> = row.iter().map(|value| escape_field(value)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    };
+    write_row(header);
+
+    for event in events {
+        let (starts_at, ends_at) = event.label_range();
+        let mut row = vec![
+            event.title.clone().unwrap_or_default(),
+            event.location.clone().unwrap_or_default(),
+            format_time(starts_at),
+        ];
+        if use_end {
+            row.push(format_time(ends_at));
+        } else if columns.duration.is_some() {
+            row.push((ends_at - starts_at).to_string());
+        }
+        if columns.date.is_some() {
+            row.push(event.date.map(|date| date.to_string()).unwrap_or_default());
+        }
+        write_row(row);
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use events;
+
+    #[test]
+    fn test_parse_rows() {
+        let text =
+            "title,start\r\n\"Sync, \"\"weekly\"\"\",10:00\r\n\r\n\"Multi\nline\",60\nLast,90";
+
+        assert_eq!(
+            parse_rows(text),
+            Ok(vec![
+                (1, vec!["title".to_string(), "start".to_string()]),
+                (2, vec!["Sync, \"weekly\"".to_string(), "10:00".to_string()]),
+                (4, vec!["Multi\nline".to_string(), "60".to_string()]),
+                (6, vec!["Last".to_string(), "90".to_string()]),
+            ])
+        );
+        assert_eq!(
+            parse_rows("title\n\"Open"),
+            Err(CsvError::new(2, "unterminated quoted field".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let text = "Title,Room,Start,End,Duration\n\
+                    Standup,Room 1,09:00,09:15,\n\
+                    Review,,60,,90\n";
+
+        assert_eq!(
+            deserialize(text, &Options::default()).unwrap(),
+            events::deserialize(
+                r#"[
+                  {"title":"Standup","starts_at":0,"duration":15},
+                  {"title":"Review","starts_at":60,"duration":90}
+                ]"#,
+                &Options::default()
+            )
+        );
+
+        // Location column is mapped by name
+        let options = Options {
+            csv_columns: CsvColumns {
+                location: "room".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            deserialize(text, &options).unwrap()[0].location,
+            Some("Room 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let text = "title,start,end,date\n\
+                    Standup,08:00,09:00,\n\
+                    Review,10:00,09:00,\n\
+                    Lunch,12:00,13:00,2026-02-30\n\
+                    Retro,14:00,,\n\
+                    Planning,15:00,16:00,2026-10-19\n";
+
+        assert_eq!(
+            read(text, &CsvColumns::default()).err(),
+            Some(vec![
+                CsvError::new(2, "invalid start \"08:00\"".to_string()),
+                CsvError::new(3, "end \"09:00\" is not after start \"10:00\"".to_string()),
+                CsvError::new(4, "invalid date \"2026-02-30\"".to_string()),
+                CsvError::new(5, "missing end or duration".to_string()),
+            ])
+        );
+        assert_eq!(
+            read("title,end\n", &CsvColumns::default()).err(),
+            Some(vec![CsvError::new(
+                1,
+                "missing column \"start\"".to_string()
+            )])
+        );
+        assert_eq!(
+            CsvError::new(3, "missing start".to_string()).to_string(),
+            "line 3: missing start"
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let events = events::deserialize(
+            r#"[
+              {"title":"Sync, \"weekly\"","location":"Room 1","starts_at":30,"duration":45,"date":"2026-10-19"},
+              {"starts_at":120,"duration":60}
+            ]"#,
+            &Options::default(),
+        );
+        let csv = write(&events, &CsvColumns::default());
+
+        assert_eq!(
+            csv,
+            "title,location,start,end,date\r\n\
+             \"Sync, \"\"weekly\"\"\",Room 1,09:30,10:15,2026-10-19\r\n\
+             ,,11:00,12:00,\r\n"
+        );
+        assert_eq!(deserialize(&csv, &Options::default()).unwrap(), events);
+
+        let columns = CsvColumns {
+            end: None,
+            date: None,
+            ..Default::default()
+        };
+        assert_eq!(
+            write(&events[1..].to_vec(), &columns),
+            "title,location,start,duration\r\n,,11:00,60\r\n"
+        );
+    }
+
+    #[test]
+    fn test_write_after_midnight() {
+        let events = events::deserialize(
+            r#"[{"title":"Late","starts_at":840,"duration":120}]"#,
+            &Options::default(),
+        );
+        let csv = write(&events, &CsvColumns::default());
+
+        assert_eq!(
+            csv,
+            "title,location,start,end,date\r\nLate,,23:00,25:00,\r\n"
+        );
+        assert_eq!(deserialize(&csv, &Options::default()).unwrap(), events);
+        assert_eq!(parse_time("48:30"), Some(39 * 60 + 30));
+        assert_eq!(parse_time("24:60"), None);
+        assert_eq!(parse_time("x24:00"), None);
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+use std::fmt::Display;
+
+/// Calendar date without time zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+impl Date {
+    pub fn new(year: i32, month: u8, day: u8) -> Date {
+        Date {
+            year: year,
+            month: month,
+            day: day,
+        }
+    }
+
+    /// Parse date in "YYYY-MM-DD" format
+    pub fn parse(date: &str) -> Option<Date> {
+        let mut parts = date.splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date::new(year, month, day))
+    }
+
+    /// Date from number of days since 1970-01-01
+    pub fn from_days(days: i32) -> Date {
+        let z = days + 719468;
+        let era = if z >= 0 { z } else { z - 146096 } / 146097;
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Date::new(year, month as u8, day as u8)
+    }
+
+    /// Number of days since 1970-01-01
+    pub fn days(&self) -> i32 {
+        let month = self.month as i32;
+        let year = if month <= 2 { self.year - 1 } else { self.year };
+        let era = if year >= 0 { year } else { year - 399 } / 400;
+        let yoe = year - era * 400;
+        let doy =
+            (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i32 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+        era * 146097 + doe - 719468
+    }
+
+    /// Day of week starting from Monday as 0
+    pub fn weekday(&self) -> u8 {
+        // 1970-01-01 was Thursday
+        (((self.days() + 3) % 7 + 7) % 7) as u8
+    }
+
+    pub fn add_days(&self, days: i32) -> Date {
+        Date::from_days(self.days() + days)
+    }
+
+    pub fn first_of_month(&self) -> Date {
+        Date::new(self.year, self.month, 1)
+    }
+}
+impl Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Parse time in "HH:MM" or "HH:MM:SS" format to minutes since midnight,
+/// times with UTC offset are rejected as the grid has no time zone
+pub fn parse_time(time: &str) -> Option<u16> {
+    let is_number =
+        |part: &str, len: usize| part.len() == len && part.chars().all(|c| c.is_ascii_digit());
+    let mut parts = time.splitn(3, ':');
+    let hour = parts
+        .next()
+        .filter(|hour| is_number(hour, 1) || is_number(hour, 2))?;
+    let minute = parts.next().filter(|minute| is_number(minute, 2))?;
+    if let Some(seconds) = parts.next() {
+        // Fraction of second is allowed and ignored
+        let whole = seconds.split('.').next().unwrap();
+        let fraction = &seconds[whole.len()..];
+        if !is_number(whole, 2) || !fraction.chars().skip(1).all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+    }
+
+    let (hour, minute): (u16, u16) = (hour.parse().ok()?, minute.parse().ok()?);
+
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+    Some(hour * 60 + minute)
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Date::parse("2026-10-19"), Some(Date::new(2026, 10, 19)));
+        assert_eq!(Date::parse("2024-02-29"), Some(Date::new(2024, 2, 29)));
+        assert_eq!(Date::parse("2026-02-29"), None);
+        assert_eq!(Date::parse("2026-13-01"), None);
+        assert_eq!(Date::parse("tomorrow"), None);
+    }
+
+    #[test]
+    fn test_days() {
+        assert_eq!(Date::new(1970, 1, 1).days(), 0);
+        assert_eq!(Date::new(2000, 3, 1).days(), 11017);
+        assert_eq!(Date::new(1969, 12, 31).days(), -1);
+
+        for days in -1000..1000 {
+            assert_eq!(Date::from_days(days * 37).days(), days * 37);
+        }
+    }
+
+    #[test]
+    fn test_weekday() {
+        assert_eq!(Date::new(1970, 1, 1).weekday(), 3);
+        assert_eq!(Date::new(2026, 10, 19).weekday(), 0);
+        assert_eq!(Date::new(2026, 10, 25).weekday(), 6);
+        assert_eq!(Date::new(1969, 12, 29).weekday(), 0);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("09:30"), Some(570));
+        assert_eq!(parse_time("9:05:59"), Some(545));
+        assert_eq!(parse_time("23:59:00.250"), Some(1439));
+        assert_eq!(parse_time("23:59Z"), None);
+        assert_eq!(parse_time("09:00:00+02:00"), None);
+        assert_eq!(parse_time("+9:00"), None);
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("noon"), None);
+    }
+
+    #[test]
+    fn test_add_days() {
+        assert_eq!(Date::new(2026, 12, 31).add_days(1), Date::new(2027, 1, 1));
+        assert_eq!(Date::new(2024, 3, 1).add_days(-1), Date::new(2024, 2, 29));
+        assert_eq!(Date::new(2026, 10, 19).to_string(), "2026-10-19");
+    }
+}
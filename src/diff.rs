@@ -0,0 +1,308 @@
+use positions;
+use svg;
+use svg::EventRect;
+use types::{Change, Event, EventDiff, EventPosition, HTMLElement, Options};
+
+const ADDED_COLOR: &'static str = "#1c7ed6";
+const CHANGED_COLOR: &'static str = "#f08c00";
+const REMOVED_COLOR: &'static str = "#e0393e";
+const GHOST_COLOR: &'static str = "#919191";
+
+fn changes(old_event: &Event, event: &Event) -> Vec<Change> {
+    let (old_starts_at, old_ends_at) = old_event.label_range();
+    let (starts_at, ends_at) = event.label_range();
+
+    let mut changes = vec![];
+    if old_starts_at != starts_at || old_event.date != event.date {
+        changes.push(Change::Moved);
+    }
+    if old_ends_at - old_starts_at != ends_at - starts_at {
+        changes.push(Change::Resized);
+    }
+    if old_event.title != event.title {
+        changes.push(Change::Retitled);
+    }
+    changes
+}
+
+/// Changed events of the new schedule followed by removed ones,
+/// events are matched by `Event::key`
+pub fn diff(old: &Vec<Event>, new: &Vec<Event>) -> Vec<EventDiff> {
+    let mut diffs = vec![];
+
+    for event in new {
+        let key = event.key();
+        let (old_id, changes) = match old.iter().find(|old_event| old_event.key() == key) {
+            Some(old_event) => (Some(old_event.source_id), changes(old_event, event)),
+            None => (None, vec![Change::Added]),
+        };
+        if !changes.is_empty() {
+            diffs.push(EventDiff {
+                key: key,
+                old_id: old_id,
+                new_id: Some(event.source_id),
+                changes: changes,
+            });
+        }
+    }
+
+    for old_event in old {
+        let key = old_event.key();
+        if !new.iter().any(|event| event.key() == key) {
+            diffs.push(EventDiff {
+                key: key,
+                old_id: Some(old_event.source_id),
+                new_id: None,
+                changes: vec![Change::Removed],
+            });
+        }
+    }
+
+    diffs
+}
+
+fn change_name(change: &Change) -> &'static str {
+    match change {
+        Change::Added => "added",
+        Change::Removed => "removed",
+        Change::Moved => "moved",
+        Change::Resized => "resized",
+        Change::Retitled => "retitled",
+    }
+}
+
+/// Event with index of the input list and its position
+fn find_source<'e, 'p>(
+    events: &'e Vec<Event>,
+    positions: &'p Vec<EventPosition>,
+    source_id: u16,
+) -> (&'e Event, &'p EventPosition) {
+    let event = events
+        .iter()
+        .find(|event| event.source_id == source_id)
+        .unwrap();
+    let pos = positions.iter().find(|pos| pos.id == event.id).unwrap();
+    (event, pos)
+}
+
+/// Frame around event box which doesn't catch pointer events
+fn create_outline<'a>(
+    rect: &EventRect,
+    color: &str,
+    dashed: bool,
+    diff: &EventDiff,
+) -> HTMLElement<'a> {
+    let mut outline = HTMLElement::new("rect")
+        .x(rect.x)
+        .y(rect.y)
+        .width(rect.width)
+        .height(rect.height)
+        .fill("none")
+        .stroke(color)
+        .stroke_width(2)
+        .pointer_events("none");
+    if dashed {
+        outline = outline.stroke_dasharray("4,4");
+    }
+
+    let changes: Vec<&str> = diff.changes.iter().map(change_name).collect();
+    let mut title = HTMLElement::new("title");
+    title.append_child(changes.join(", "));
+    outline.append_child(title);
+
+    outline
+}
+
+fn create_layer<'a>(label: &str, options: &Options) -> HTMLElement<'a> {
+    let (x, y) = svg::events_offset(options);
+    HTMLElement::new("g").translate(x, y).aria_label(label)
+}
+
+/// New schedule with ghost outlines at old positions of moved, resized
+/// and removed events and highlighted changed events
+pub fn render(old: &Vec<Event>, new: &Vec<Event>, options: &Options) -> String {
+    let diffs = diff(old, new);
+    let old_positions = positions::layout(old.clone(), options);
+    let new_positions = positions::layout(new.clone(), options);
+
+    let mut svg = svg::create_svg_container(options);
+    let (title, desc) = svg::create_summary(new.len(), options);
+    svg.append_child(title);
+    svg.append_child(desc);
+    if let Some(defs) = svg::create_defs(new) {
+        svg.append_child(defs);
+    }
+    svg.append_child(svg::create_grid_container(options));
+
+    let mut ghosts = create_layer("Previous positions", options);
+    for diff in &diffs {
+        let old_id = match diff.old_id {
+            Some(old_id) => old_id,
+            None => continue,
+        };
+        let color = if diff.changes.contains(&Change::Removed) {
+            REMOVED_COLOR
+        } else if diff.changes.contains(&Change::Moved) || diff.changes.contains(&Change::Resized) {
+            GHOST_COLOR
+        } else {
+            continue;
+        };
+
+        let (old_event, pos) = find_source(old, &old_positions, old_id);
+        let rect = svg::event_rect(old_event, pos, options);
+        ghosts.append_child(create_outline(&rect, color, true, diff));
+    }
+    svg.append_child(ghosts);
+
+    let mut events_container = svg::create_events_container(options);
+    for pos in svg::ordered_positions(new, &new_positions, options) {
+        let (clip_path, event_container) = svg::create_event(&new[pos.id as usize], pos, options);
+        svg.append_child(clip_path);
+        events_container.append_child(event_container);
+    }
+    svg.append_child(events_container);
+
+    let mut highlights = create_layer("Changes", options);
+    for diff in &diffs {
+        if let Some(new_id) = diff.new_id {
+            let color = if diff.old_id.is_none() {
+                ADDED_COLOR
+            } else {
+                CHANGED_COLOR
+            };
+            let (event, pos) = find_source(new, &new_positions, new_id);
+            let rect = svg::event_rect(event, pos, options);
+            highlights.append_child(create_outline(&rect, color, false, diff));
+        }
+    }
+    svg.append_child(highlights);
+
+    svg::markup(&svg, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: u16, uid: &str, starts_at: u16, duration: u16, title: &str) -> Event {
+        Event {
+            id: id,
+            source_id: id,
+            uid: Some(uid.to_string()),
+            starts_at: starts_at,
+            duration: duration,
+            title: Some(title.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = vec![
+            event(0, "standup", 0, 15, "Standup"),
+            event(1, "review", 60, 60, "Review"),
+            event(2, "lunch", 180, 60, "Lunch"),
+            event(3, "retro", 300, 60, "Retro"),
+        ];
+        let new = vec![
+            event(0, "review", 90, 30, "Design review"),
+            event(1, "standup", 0, 15, "Standup"),
+            event(2, "lunch", 180, 45, "Lunch"),
+            event(3, "planning", 360, 60, "Planning"),
+        ];
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                EventDiff {
+                    key: "uid:review".to_string(),
+                    old_id: Some(1),
+                    new_id: Some(0),
+                    changes: vec![Change::Moved, Change::Resized, Change::Retitled],
+                },
+                EventDiff {
+                    key: "uid:lunch".to_string(),
+                    old_id: Some(2),
+                    new_id: Some(2),
+                    changes: vec![Change::Resized],
+                },
+                EventDiff {
+                    key: "uid:planning".to_string(),
+                    old_id: None,
+                    new_id: Some(3),
+                    changes: vec![Change::Added],
+                },
+                EventDiff {
+                    key: "uid:retro".to_string(),
+                    old_id: Some(3),
+                    new_id: None,
+                    changes: vec![Change::Removed],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_without_uid() {
+        let mut old = event(0, "", 0, 30, "Standup");
+        old.uid = None;
+        let mut new = old.clone();
+        new.starts_at = 15;
+
+        assert_eq!(
+            diff(&vec![old], &vec![new]),
+            vec![EventDiff {
+                key: "idx:0".to_string(),
+                old_id: Some(0),
+                new_id: Some(0),
+                changes: vec![Change::Moved],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_uid_like_index() {
+        let mut old = event(0, "", 0, 30, "Standup");
+        old.uid = None;
+        let new = event(0, "0", 0, 30, "Standup");
+
+        assert_eq!(
+            diff(&vec![old], &vec![new]),
+            vec![
+                EventDiff {
+                    key: "uid:0".to_string(),
+                    old_id: None,
+                    new_id: Some(0),
+                    changes: vec![Change::Added],
+                },
+                EventDiff {
+                    key: "idx:0".to_string(),
+                    old_id: Some(0),
+                    new_id: None,
+                    changes: vec![Change::Removed],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let old = vec![
+            event(0, "review", 60, 60, "Review"),
+            event(1, "retro", 300, 60, "Retro"),
+        ];
+        let new = vec![event(0, "review", 120, 60, "Review")];
+        let svg = render(&old, &new, &Options::default());
+
+        // Ghosts at old positions of moved and removed events
+        assert!(svg.contains(&format!(
+            "<rect fill=\"none\" height=\"60\" pointer-events=\"none\" stroke=\"{}\" \
+             stroke-dasharray=\"4,4\" stroke-width=\"2\" width=\"580\" x=\"0\" y=\"60\">\
+             <title>moved</title></rect>",
+            GHOST_COLOR
+        )));
+        assert!(svg.contains(&format!("stroke=\"{}\"", REMOVED_COLOR)));
+        assert!(svg.contains(&format!("stroke=\"{}\"", CHANGED_COLOR)));
+        assert!(!svg.contains("Retro"));
+    }
+}
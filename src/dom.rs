@@ -0,0 +1,52 @@
+extern crate web_sys;
+
+use self::web_sys::Document;
+pub use self::web_sys::Element;
+use svg::SVG_NS;
+use types::{HTMLElement, Visitor};
+use wasm_bindgen::JsValue;
+
+/// Creates DOM nodes of visited elements, each element is attached
+/// to its parent once all of its children are in place
+struct DomBuilder {
+    document: Document,
+    parents: Vec<Element>,
+}
+impl Visitor for DomBuilder {
+    type Error = JsValue;
+
+    fn open(&mut self, tag: &str, attrs: &[(&str, &str)]) -> Result<(), JsValue> {
+        let el = self.document.create_element_ns(Some(SVG_NS), tag)?;
+        // Namespace comes from create_element_ns
+        for (attr, value) in attrs.iter().filter(|(attr, _)| *attr != "xmlns") {
+            el.set_attribute(attr, value)?;
+        }
+        self.parents.push(el);
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str) -> Result<(), JsValue> {
+        let node = self.document.create_text_node(text);
+        self.parents.last().unwrap().append_child(&node)?;
+        Ok(())
+    }
+
+    fn close(&mut self, _tag: &str) -> Result<(), JsValue> {
+        let el = self.parents.pop().unwrap();
+        self.parents.last().unwrap().append_child(&el)?;
+        Ok(())
+    }
+}
+
+/// Replace content of the container with the element tree
+pub fn render(root: &HTMLElement, container: &Element) -> Result<(), JsValue> {
+    let document = container
+        .owner_document()
+        .ok_or_else(|| JsValue::from_str("container is not in a document"))?;
+
+    container.set_inner_html("");
+    root.visit(&mut DomBuilder {
+        document: document,
+        parents: vec![container.clone()],
+    })
+}
@@ -1,19 +1,233 @@
 extern crate serde_json;
 
-use types::{Event, ExternalEvent};
+use date::Date;
+use recurrence::{self, Rule};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use types::{Event, ExternalEvent, Options, Snap, SnapMode};
 
-pub fn deserialize(events_json: &str) -> Vec<Event> {
+fn snap_time(time: u16, snap: &Snap) -> u16 {
+    let rest = time % snap.interval;
+    match snap.mode {
+        SnapMode::Floor => time - rest,
+        SnapMode::Ceil if rest > 0 => time - rest + snap.interval,
+        SnapMode::Ceil => time,
+        SnapMode::Nearest if rest * 2 >= snap.interval => time - rest + snap.interval,
+        SnapMode::Nearest => time - rest,
+    }
+}
+
+/// Align event to the grid, original times are kept for labels
+pub fn snap(event: &mut Event, snap: &Snap) {
+    if snap.interval == 0 {
+        return;
+    }
+
+    let starts_at = snap_time(event.starts_at, snap);
+    let mut ends_at = snap_time(event.ends_at(), snap);
+    if ends_at <= starts_at {
+        ends_at = starts_at + snap.interval;
+    }
+
+    event.label_time = Some(event.label_range());
+    event.starts_at = starts_at;
+    event.duration = ends_at - starts_at;
+}
+
+/// Replace details of private events so nothing of them gets rendered,
+/// uid is replaced by its hash to keep the event recognizable between renders
+pub fn mask(event: &mut Event, busy_label: &str) {
+    event.title = Some(busy_label.to_string());
+    event.location = None;
+    event.resource = None;
+    event.category = None;
+    event.extra.clear();
+    event.uid = event.uid.take().map(|uid| {
+        let mut hasher = DefaultHasher::new();
+        uid.hash(&mut hasher);
+        format!("private-{:016x}", hasher.finish())
+    });
+}
+
+/// Hide private events, or all events with `privacy` option, before
+/// rendering. Exports keep full data of the user's own events.
+pub fn mask_private(events: &mut Vec<Event>, options: &Options) {
+    for event in events.iter_mut() {
+        if options.privacy || event.private {
+            mask(event, &options.busy_label);
+        }
+    }
+}
+
+pub fn deserialize(events_json: &str, options: &Options) -> Vec<Event> {
     let raw_events: Vec<ExternalEvent> = serde_json::from_str(events_json).unwrap();
 
+    from_external(raw_events, options)
+}
+
+/// Events in the same shape no matter which format they were read from,
+/// invalid dates are ignored and unsupported rules make events non-recurring
+pub fn from_external(raw_events: Vec<ExternalEvent>, options: &Options) -> Vec<Event> {
     raw_events
         .into_iter()
         .enumerate()
-        .map(|(id, raw_event)| Event {
-            id: id as u16,
-            starts_at: raw_event.starts_at,
-            duration: raw_event.duration,
-            title: raw_event.title,
-            location: raw_event.location,
+        .map(|(id, raw_event)| {
+            let mut event = Event {
+                id: id as u16,
+                source_id: id as u16,
+                uid: raw_event.uid,
+                starts_at: raw_event.starts_at,
+                duration: raw_event.duration,
+                title: raw_event.title,
+                location: raw_event.location,
+                resource: raw_event.resource,
+                category: raw_event.category,
+                date: raw_event.date.and_then(|date| Date::parse(&date)),
+                label_time: None,
+                status: raw_event.status,
+                private: raw_event.private,
+                extra: raw_event.extra,
+                recurrence: raw_event.rrule.and_then(|rule| Rule::parse(&rule)),
+                excluded_dates: raw_event
+                    .exdate
+                    .iter()
+                    .filter_map(|date| {
+                        Date::parse(date).or_else(|| recurrence::parse_basic_date(date))
+                    })
+                    .collect(),
+            };
+            if let Some(ref snap_options) = options.snap {
+                snap(&mut event, snap_options);
+            }
+            event
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapped(starts_at: u16, duration: u16, interval: u16, mode: SnapMode) -> (u16, u16) {
+        let mut event = Event {
+            starts_at: starts_at,
+            duration: duration,
+            ..Default::default()
+        };
+        snap(
+            &mut event,
+            &Snap {
+                interval: interval,
+                mode: mode,
+            },
+        );
+        (event.starts_at, event.duration)
+    }
+
+    #[test]
+    fn test_snap() {
+        assert_eq!(snapped(7, 46, 15, SnapMode::Nearest), (0, 60));
+        assert_eq!(snapped(8, 46, 15, SnapMode::Nearest), (15, 45));
+        assert_eq!(snapped(7, 46, 15, SnapMode::Floor), (0, 45));
+        assert_eq!(snapped(7, 46, 15, SnapMode::Ceil), (15, 45));
+        assert_eq!(snapped(30, 30, 15, SnapMode::Ceil), (30, 30));
+        // Event never collapses to zero duration
+        assert_eq!(snapped(61, 3, 10, SnapMode::Nearest), (60, 10));
+    }
+
+    #[test]
+    fn test_deserialize_snap() {
+        let options = Options {
+            snap: Some(Snap {
+                interval: 5,
+                mode: SnapMode::Floor,
+            }),
+            ..Default::default()
+        };
+        let events = deserialize(r#"[{"starts_at":7,"duration":30}]"#, &options);
+
+        assert_eq!((events[0].starts_at, events[0].duration), (5, 30));
+        assert_eq!(events[0].label_range(), (7, 37));
+    }
+
+    #[test]
+    fn test_deserialize_invalid_dates() {
+        let events = deserialize(
+            r#"[
+              {"starts_at":0,"duration":30,"date":"2026-10-19","rrule":"FREQ=YEARLY"},
+              {"starts_at":0,"duration":30,"date":"2026-02-30","rrule":"FREQ=DAILY;COUNT=x"},
+              {"starts_at":0,"duration":30,"date":"2026-10-19","rrule":"FREQ=DAILY","exdate":["tomorrow","20261020"]}
+            ]"#,
+            &Options::default(),
+        );
+
+        assert_eq!(events[0].recurrence, None);
+        assert_eq!(events[0].date, Some(Date::new(2026, 10, 19)));
+        assert_eq!(
+            (events[1].date, events[1].recurrence.is_none()),
+            (None, true)
+        );
+        assert!(events[2].recurrence.is_some());
+        assert_eq!(events[2].excluded_dates, vec![Date::new(2026, 10, 20)]);
+    }
+
+    #[test]
+    fn test_mask() {
+        let mut event = Event {
+            uid: Some("interview-with-alice".to_string()),
+            resource: Some("Alice".to_string()),
+            category: Some("hiring".to_string()),
+            ..Default::default()
+        };
+        let mut same_uid = event.clone();
+        mask(&mut event, "Busy");
+        mask(&mut same_uid, "Busy");
+
+        assert_eq!(event.resource, None);
+        assert_eq!(event.category, None);
+        let uid = event.uid.clone().unwrap();
+        assert!(uid.starts_with("private-") && !uid.contains("alice"));
+        assert_eq!(same_uid.uid, event.uid);
+    }
+
+    #[test]
+    fn test_mask_private() {
+        let events_json = r#"[
+          {"title":"Interview","location":"Room 1","starts_at":0,"duration":30,"private":true},
+          {"title":"Standup","location":"Room 2","starts_at":60,"duration":15}
+        ]"#;
+        let labels = |options: &Options| -> Vec<(Option<String>, Option<String>)> {
+            let mut events = deserialize(events_json, options);
+            mask_private(&mut events, options);
+            events
+                .into_iter()
+                .map(|event| (event.title, event.location))
+                .collect()
+        };
+
+        assert_eq!(
+            labels(&Options::default()),
+            vec![
+                (Some("Busy".to_string()), None),
+                (Some("Standup".to_string()), Some("Room 2".to_string())),
+            ]
+        );
+        assert_eq!(
+            labels(&Options {
+                privacy: true,
+                busy_label: "Occupied".to_string(),
+                ..Default::default()
+            }),
+            vec![
+                (Some("Occupied".to_string()), None),
+                (Some("Occupied".to_string()), None),
+            ]
+        );
+
+        // Nothing is hidden until events are rendered
+        assert_eq!(
+            deserialize(events_json, &Options::default())[0].title,
+            Some("Interview".to_string())
+        );
+    }
+}
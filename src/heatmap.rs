@@ -0,0 +1,247 @@
+use resources;
+use svg;
+use types::{Event, EventPosition, HTMLElement, Options, Orientation, Status};
+
+const LEGEND_SWATCH_SIZE: u16 = 12;
+const LEGEND_ITEM_WIDTH: u16 = 60;
+const LEGEND_MARGIN: u16 = 20;
+
+/// Events grouped by person, events without resource don't belong
+/// to anybody and are left out instead of making up one more person
+fn group_people(events: &Vec<Event>, options: &Options) -> Vec<(String, Vec<Event>)> {
+    let assigned: Vec<Event> = events
+        .iter()
+        .filter(|event| event.resource_name().is_some())
+        .cloned()
+        .collect();
+    resources::group(&assigned, &options.resources)
+}
+
+/// Number of people with events in each bucket of the grid
+pub fn busy_counts(people: &Vec<(String, Vec<Event>)>, bucket: u16) -> Vec<usize> {
+    (0..svg::grid_duration())
+        .step_by(bucket as usize)
+        .map(|starts_at| {
+            let cell = Event {
+                starts_at: starts_at,
+                duration: bucket,
+                ..Default::default()
+            };
+            people
+                .iter()
+                .filter(|(_, events)| {
+                    events.iter().any(|event| match event.status {
+                        Status::Cancelled | Status::Declined => false,
+                        Status::Confirmed | Status::Tentative => event.overlap(&cell),
+                    })
+                })
+                .count()
+        })
+        .collect()
+}
+
+/// Color of the ramp for share of busy people, free cells have no color
+pub fn cell_color(busy: usize, people: usize, colors: &Vec<String>) -> Option<&String> {
+    if busy == 0 || people == 0 || colors.is_empty() {
+        return None;
+    }
+    let level = (busy * colors.len()).div_ceil(people);
+    colors.get(level.min(colors.len()) - 1)
+}
+
+fn create_cell<'a>(
+    starts_at: u16,
+    bucket: u16,
+    busy: usize,
+    people: usize,
+    fill: &str,
+    options: &Options,
+) -> HTMLElement<'a> {
+    let cell = Event {
+        starts_at: starts_at,
+        duration: bucket,
+        ..Default::default()
+    };
+    let pos = EventPosition {
+        id: 0,
+        width_multiplier: 1.0,
+        offset: 0,
+        indent: 0,
+    };
+    let rect = svg::event_rect(&cell, &pos, options);
+
+    let mut el = HTMLElement::new("rect")
+        .x(rect.x)
+        .y(rect.y)
+        .width(rect.width)
+        .height(rect.height)
+        .fill(fill);
+    let mut title = HTMLElement::new("title");
+    title.append_child(format!(
+        "{}: {} of {} busy",
+        svg::format_time_range(starts_at, starts_at + bucket),
+        busy,
+        people
+    ));
+    el.append_child(title);
+    el
+}
+
+/// Swatches of the ramp with share of busy people each color stands for
+fn create_legend<'a>(colors: &Vec<String>, options: &Options) -> HTMLElement<'a> {
+    let offset = match options.orientation {
+        Orientation::Vertical => svg::SVG_HEIGHT,
+        Orientation::Horizontal => svg::SVG_WIDTH,
+    } + LEGEND_MARGIN;
+    let mut g = HTMLElement::new("g")
+        .translate(0, offset)
+        .aria_label("Legend");
+
+    for (idx, color) in colors.iter().enumerate() {
+        let x = idx as u16 * LEGEND_ITEM_WIDTH;
+        g.append_child(
+            HTMLElement::new("rect")
+                .x(x)
+                .width(LEGEND_SWATCH_SIZE)
+                .height(LEGEND_SWATCH_SIZE)
+                .fill(color),
+        );
+
+        let mut label = HTMLElement::new("text")
+            .x(x + LEGEND_SWATCH_SIZE + 4)
+            .y(LEGEND_SWATCH_SIZE / 2)
+            .dy(".35em")
+            .fill("#919191");
+        label.append_child(format!("≤{}%", (idx + 1) * 100 / colors.len()));
+        g.append_child(label);
+    }
+
+    g
+}
+
+pub fn render(events: &Vec<Event>, options: &Options) -> String {
+    let people = group_people(events, options);
+    let bucket = options.heatmap_bucket.max(1);
+
+    // Legend is placed below the grid and needs room in the view box
+    let (width, height) = match options.orientation {
+        Orientation::Vertical => (svg::SVG_WIDTH, svg::SVG_HEIGHT),
+        Orientation::Horizontal => (svg::SVG_HEIGHT, svg::SVG_WIDTH),
+    };
+    let width = width.max(options.heatmap_colors.len() as u16 * LEGEND_ITEM_WIDTH);
+    let height = height + LEGEND_MARGIN + LEGEND_SWATCH_SIZE;
+    let mut svg = svg::create_svg_container(options)
+        .width(width)
+        .height(height)
+        .view_box(width, height);
+    let (title, desc) = svg::create_summary(events.len(), options);
+    svg.append_child(title);
+    svg.append_child(desc);
+    svg.append_child(svg::create_grid_container(options));
+
+    let (x, y) = svg::events_offset(options);
+    let mut cells = HTMLElement::new("g")
+        .translate(x, y)
+        .aria_label("Busy people");
+    for (idx, busy) in busy_counts(&people, bucket).into_iter().enumerate() {
+        if let Some(fill) = cell_color(busy, people.len(), &options.heatmap_colors) {
+            let starts_at = idx as u16 * bucket;
+            let duration = bucket.min(svg::grid_duration() - starts_at);
+            cells.append_child(create_cell(
+                starts_at,
+                duration,
+                busy,
+                people.len(),
+                fill,
+                options,
+            ));
+        }
+    }
+    svg.append_child(cells);
+
+    svg.append_child(create_legend(&options.heatmap_colors, options));
+
+    svg::markup(&svg, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(starts_at: u16, duration: u16, resource: &str) -> Event {
+        Event {
+            starts_at: starts_at,
+            duration: duration,
+            resource: Some(resource.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_busy_counts() {
+        let mut declined = event(0, 60, "Bob");
+        declined.status = Status::Declined;
+        let events = vec![
+            event(0, 90, "Alice"),
+            event(30, 30, "Alice"),
+            event(60, 15, "Bob"),
+            declined,
+        ];
+        let people = resources::group(&events, &vec![]);
+
+        assert_eq!(&busy_counts(&people, 30)[..4], &[1, 1, 2, 0]);
+        assert_eq!(busy_counts(&people, 30).len(), 24);
+        assert_eq!(busy_counts(&people, 90).len(), 8);
+    }
+
+    #[test]
+    fn test_group_people_unassigned() {
+        let events = vec![
+            event(0, 90, "Alice"),
+            Event {
+                starts_at: 0,
+                duration: 30,
+                ..Default::default()
+            },
+            Event {
+                starts_at: 60,
+                duration: 30,
+                ..Default::default()
+            },
+        ];
+        let people = group_people(&events, &Options::default());
+
+        assert_eq!(people.len(), 1);
+        assert_eq!(&busy_counts(&people, 30)[..4], &[1, 1, 1, 0]);
+        assert!(render(&events, &Options::default()).contains("9 AM – 9:30 AM: 1 of 1 busy"));
+    }
+
+    #[test]
+    fn test_cell_color() {
+        let colors: Vec<String> = vec!["light".to_string(), "dark".to_string()];
+
+        assert_eq!(cell_color(0, 4, &colors), None);
+        assert_eq!(cell_color(1, 4, &colors), Some(&colors[0]));
+        assert_eq!(cell_color(2, 4, &colors), Some(&colors[0]));
+        assert_eq!(cell_color(3, 4, &colors), Some(&colors[1]));
+        assert_eq!(cell_color(4, 4, &colors), Some(&colors[1]));
+        assert_eq!(cell_color(1, 1, &vec![]), None);
+    }
+
+    #[test]
+    fn test_render_legend_in_view_box() {
+        let events = vec![event(0, 90, "Alice")];
+        let html = render(&events, &Options::default());
+        assert!(html.contains("transform=\"translate(0,740)\""));
+        assert!(html.contains("viewBox=\"0 0 600 752\""));
+
+        let options = Options {
+            orientation: Orientation::Horizontal,
+            heatmap_colors: (0..12).map(|idx| format!("#{:06x}", idx)).collect(),
+            ..Default::default()
+        };
+        let html = render(&events, &options);
+        assert!(html.contains("transform=\"translate(0,620)\""));
+        assert!(html.contains("viewBox=\"0 0 720 632\""));
+    }
+}
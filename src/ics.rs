@@ -0,0 +1,440 @@
+use date::{self, Date};
+use recurrence::{Frequency, Rule};
+use svg;
+use types::{Event, Status};
+
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Escape TEXT value as described in RFC 5545 section 3.3.11
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Split content line into lines of at most 75 octets,
+/// continuation lines start with a space
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_length = 0;
+
+    for c in line.chars() {
+        if line_length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
+
+/// How times of the grid are written
+#[derive(Debug, PartialEq)]
+enum TimeZone {
+    Floating,
+    /// Grid is in local time with the offset in minutes, times are written in UTC
+    Utc(i32),
+}
+
+/// Empty string and names of zones give floating time as there is no
+/// VTIMEZONE data to reference them, "UTC", "Z" and "+02:00" give UTC
+fn parse_tz(tz: &str) -> TimeZone {
+    match tz {
+        "UTC" | "Z" => TimeZone::Utc(0),
+        tz if tz.starts_with('+') || tz.starts_with('-') => {
+            let sign = if tz.starts_with('-') { -1 } else { 1 };
+            match date::parse_time(&tz[1..]) {
+                Some(offset) => TimeZone::Utc(sign * offset as i32),
+                None => TimeZone::Floating,
+            }
+        }
+        _ => TimeZone::Floating,
+    }
+}
+
+fn format_minutes(day: Date, minutes: i32, seconds: u8, zone: &TimeZone) -> String {
+    let minutes = match *zone {
+        TimeZone::Floating => minutes,
+        TimeZone::Utc(offset) => minutes - offset,
+    };
+    let day = day.add_days(minutes.div_euclid(24 * 60));
+    let minutes = minutes.rem_euclid(24 * 60);
+    let value = format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}",
+        day.year,
+        day.month,
+        day.day,
+        minutes / 60,
+        minutes % 60,
+        seconds
+    );
+
+    match *zone {
+        TimeZone::Floating => value,
+        TimeZone::Utc(_) => format!("{}Z", value),
+    }
+}
+
+/// Date and time of minute since start of the grid on the day
+fn format_date_time(day: Date, minute: u16, zone: &TimeZone) -> String {
+    format_minutes(day, svg::FIRST_HOUR as i32 * 60 + minute as i32, 0, zone)
+}
+
+/// DTSTAMP value of UTC time like "2026-10-19T08:15:00Z" or
+/// "2026-10-19T08:15:00.000Z" returned by `Date.prototype.toISOString`
+pub fn format_stamp(timestamp: &str) -> Option<String> {
+    let idx = timestamp.find('T')?;
+    let day = Date::parse(&timestamp[..idx])?;
+    let time = timestamp[idx + 1..].trim_end_matches('Z');
+    if time.len() + idx + 2 != timestamp.len() {
+        return None;
+    }
+    let minutes = date::parse_time(time)?;
+    let seconds = time
+        .get(6..8)
+        .map_or(Some(0), |seconds| seconds.parse().ok())?;
+
+    Some(format_minutes(
+        day,
+        minutes as i32,
+        seconds,
+        &TimeZone::Utc(0),
+    ))
+}
+
+/// RRULE value, UNTIL is written in the same form as DTSTART
+fn format_rule(rule: &Rule, zone: &TimeZone) -> String {
+    let mut parts = vec![format!(
+        "FREQ={}",
+        match rule.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        }
+    )];
+    if rule.interval > 1 {
+        parts.push(format!("INTERVAL={}", rule.interval));
+    }
+    if !rule.by_day.is_empty() {
+        let days: Vec<String> = rule.by_day.iter().map(|day| day.to_string()).collect();
+        parts.push(format!("BYDAY={}", days.join(",")));
+    }
+    if let Some(count) = rule.count {
+        parts.push(format!("COUNT={}", count));
+    }
+    if let Some(until) = rule.until {
+        // Last day of the rule is included
+        parts.push(format!(
+            "UNTIL={}",
+            format_minutes(until, 24 * 60 - 1, 59, zone)
+        ));
+    }
+    parts.join(";")
+}
+
+/// Serialize events into VCALENDAR, events without own date take place
+/// on `day`, recurring events keep their RRULE and EXDATE. Named time zones
+/// are written as floating time with X-WR-TIMEZONE since VTIMEZONE data
+/// is not available, `stamp` is DTSTAMP value of all events returned
+/// by `format_stamp`.
+pub fn serialize(events: &Vec<Event>, day: Date, tz: &str, stamp: &str) -> String {
+    let zone = parse_tz(tz);
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//events_matrix//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    if zone == TimeZone::Floating && !tz.is_empty() {
+        lines.push(format!("X-WR-TIMEZONE:{}", escape_text(tz)));
+    }
+
+    for event in events {
+        let event_day = event.date.unwrap_or(day);
+        let (starts_at, ends_at) = event.label_range();
+
+        lines.push("BEGIN:VEVENT".to_string());
+        match event.uid {
+            Some(ref uid) => lines.push(format!("UID:{}", escape_text(uid))),
+            None => lines.push(format!(
+                "UID:{}-{}@events_matrix",
+                event.source_id, event_day
+            )),
+        }
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!(
+            "DTSTART:{}",
+            format_date_time(event_day, starts_at, &zone)
+        ));
+        lines.push(format!(
+            "DTEND:{}",
+            format_date_time(event_day, ends_at, &zone)
+        ));
+        if let Some(ref rule) = event.recurrence {
+            lines.push(format!("RRULE:{}", format_rule(rule, &zone)));
+            for excluded in &event.excluded_dates {
+                lines.push(format!(
+                    "EXDATE:{}",
+                    format_date_time(*excluded, starts_at, &zone)
+                ));
+            }
+        }
+        if let Some(ref title) = event.title {
+            lines.push(format!("SUMMARY:{}", escape_text(title)));
+        }
+        if let Some(ref location) = event.location {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if event.private {
+            lines.push("CLASS:PRIVATE".to_string());
+        }
+        match event.status {
+            Status::Tentative => lines.push("STATUS:TENTATIVE".to_string()),
+            Status::Cancelled => lines.push("STATUS:CANCELLED".to_string()),
+            Status::Confirmed | Status::Declined => {}
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use recurrence::WeekdayNum;
+    use std::collections::BTreeMap;
+
+    const STAMP: &'static str = "20261018T081500Z";
+
+    /// Minimal reader of VEVENT properties used to check round trip
+    fn parse(ics: &str) -> Vec<BTreeMap<String, String>> {
+        let unfolded = ics.replace("\r\n ", "");
+        let mut events = vec![];
+        let mut current: Option<BTreeMap<String, String>> = None;
+
+        for line in unfolded.split("\r\n").filter(|line| !line.is_empty()) {
+            match line {
+                "BEGIN:VEVENT" => current = Some(BTreeMap::new()),
+                "END:VEVENT" => events.push(current.take().unwrap()),
+                _ => {
+                    if let Some(ref mut event) = current {
+                        let idx = line.find(':').unwrap();
+                        let mut value = String::new();
+                        let mut chars = line[idx + 1..].chars();
+                        while let Some(c) = chars.next() {
+                            match c {
+                                '\\' => match chars.next().unwrap() {
+                                    'n' | 'N' => value.push('\n'),
+                                    c => value.push(c),
+                                },
+                                c => value.push(c),
+                            }
+                        }
+                        event.insert(line[..idx].to_string(), value);
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    fn event(id: u16, starts_at: u16, duration: u16, title: &str) -> Event {
+        Event {
+            id: id,
+            source_id: id,
+            starts_at: starts_at,
+            duration: duration,
+            title: Some(title.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(
+            escape_text("Sync; design, review\\notes\nnext"),
+            "Sync\\; design\\, review\\\\notes\\nnext"
+        );
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = "a".repeat(80);
+        assert_eq!(
+            fold_line(&line),
+            format!("{}\r\n {}\r\n", "a".repeat(75), "a".repeat(5))
+        );
+
+        // Multi-byte characters are never split
+        let line = format!("{}{}", "a".repeat(74), "ж");
+        assert_eq!(fold_line(&line), format!("{}\r\n ж\r\n", "a".repeat(74)));
+        for folded in fold_line(&"ж".repeat(100)).split("\r\n") {
+            assert!(folded.len() <= 75);
+        }
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut standup = event(0, 30, 15, "Standup");
+        standup.location = Some("Room 1".to_string());
+        standup.status = Status::Tentative;
+
+        assert_eq!(
+            serialize(
+                &vec![standup],
+                Date::new(2026, 10, 19),
+                "Europe/Kyiv",
+                STAMP
+            ),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//events_matrix//EN\r\n\
+             CALSCALE:GREGORIAN\r\n\
+             X-WR-TIMEZONE:Europe/Kyiv\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:0-2026-10-19@events_matrix\r\n\
+             DTSTAMP:20261018T081500Z\r\n\
+             DTSTART:20261019T093000\r\n\
+             DTEND:20261019T094500\r\n\
+             SUMMARY:Standup\r\n\
+             LOCATION:Room 1\r\n\
+             STATUS:TENTATIVE\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let title =
+            "Planning; Q4, \"roadmap\" \\ notes\nand a very long description which needs folding ✓";
+        let mut planning = event(0, 60, 90, title);
+        planning.location = Some("Room 1, 2nd floor".to_string());
+        planning.uid = Some("planning@example.com".to_string());
+        let mut late = event(1, 840, 120, "Late");
+        late.date = Some(Date::new(2026, 10, 20));
+
+        let ics = serialize(
+            &vec![planning, late],
+            Date::new(2026, 10, 19),
+            "UTC",
+            "20261019T081500Z",
+        );
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= 75);
+        }
+
+        let events = parse(&ics);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["UID"], "planning@example.com");
+        assert_eq!(events[0]["SUMMARY"], title);
+        assert_eq!(events[0]["LOCATION"], "Room 1, 2nd floor");
+        assert_eq!(events[0]["DTSTART"], "20261019T100000Z");
+        assert_eq!(events[0]["DTEND"], "20261019T113000Z");
+        // Event crosses midnight of its own date
+        assert_eq!(events[1]["DTSTART"], "20261020T230000Z");
+        assert_eq!(events[1]["DTEND"], "20261021T010000Z");
+    }
+
+    #[test]
+    fn test_serialize_offset() {
+        let mut early = event(0, 0, 60, "Early");
+        early.date = Some(Date::new(2026, 1, 1));
+        let ics = serialize(&vec![early], Date::new(2026, 10, 19), "+10:00", STAMP);
+
+        let events = parse(&ics);
+        assert!(!ics.contains("TZID"));
+        assert_eq!(events[0]["DTSTART"], "20251231T230000Z");
+        assert_eq!(events[0]["DTEND"], "20260101T000000Z");
+        assert_eq!(events[0]["DTSTAMP"], STAMP);
+
+        let events = parse(&serialize(
+            &vec![event(0, 0, 60, "Early")],
+            Date::new(2026, 10, 19),
+            "-03:30",
+            STAMP,
+        ));
+        assert_eq!(events[0]["DTSTART"], "20261019T123000Z");
+    }
+
+    #[test]
+    fn test_serialize_recurrence() {
+        let mut standup = event(0, 30, 15, "Standup");
+        standup.date = Some(Date::new(2026, 10, 19));
+        standup.recurrence = Some(Rule {
+            frequency: Frequency::Weekly,
+            interval: 2,
+            by_day: vec![
+                WeekdayNum {
+                    ordinal: None,
+                    weekday: 0,
+                },
+                WeekdayNum {
+                    ordinal: Some(-1),
+                    weekday: 4,
+                },
+            ],
+            count: None,
+            until: Some(Date::new(2026, 12, 31)),
+        });
+        standup.excluded_dates = vec![Date::new(2026, 11, 2)];
+
+        let events = parse(&serialize(
+            &vec![standup.clone()],
+            Date::new(2026, 10, 19),
+            "UTC",
+            STAMP,
+        ));
+        assert_eq!(
+            events[0]["RRULE"],
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,-1FR;UNTIL=20261231T235959Z"
+        );
+        assert_eq!(events[0]["EXDATE"], "20261102T093000Z");
+
+        standup.recurrence.as_mut().unwrap().until = None;
+        standup.recurrence.as_mut().unwrap().count = Some(5);
+        let events = parse(&serialize(
+            &vec![standup],
+            Date::new(2026, 10, 19),
+            "",
+            STAMP,
+        ));
+        assert_eq!(
+            events[0]["RRULE"],
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,-1FR;COUNT=5"
+        );
+        assert_eq!(events[0]["EXDATE"], "20261102T093000");
+    }
+
+    #[test]
+    fn test_format_stamp() {
+        assert_eq!(
+            format_stamp("2026-10-19T08:15:42.123Z"),
+            Some("20261019T081542Z".to_string())
+        );
+        assert_eq!(
+            format_stamp("2026-10-19T08:15:00Z"),
+            Some("20261019T081500Z".to_string())
+        );
+        assert_eq!(format_stamp("2026-10-19T08:15:00+02:00"), None);
+        assert_eq!(format_stamp("2026-10-19"), None);
+        assert_eq!(format_stamp("yesterday"), None);
+        assert_eq!(format_stamp(""), None);
+    }
+}
@@ -9,29 +9,300 @@ extern crate serde_derive;
 extern crate lazy_static;
 
 extern crate serde;
+extern crate serde_json;
 extern crate wasm_bindgen;
 
+mod agenda;
+mod csv;
+mod date;
+mod diff;
+#[cfg(feature = "dom")]
+mod dom;
 mod events;
+mod heatmap;
+mod ics;
 mod matrix;
+mod month;
+mod options;
+mod patch;
 mod positions;
+mod recurrence;
+mod resources;
+mod stats;
 mod svg;
 mod types;
 
+use date::Date;
+use types::{Event, EventPosition, Options};
 use wasm_bindgen::prelude::*;
 
+/// Events to render with private details hidden
+fn visible_events(events_str: &str, options: &Options) -> Vec<Event> {
+    let mut events_vec = events::deserialize(events_str, options);
+    events::mask_private(&mut events_vec, options);
+    events_vec
+}
+
+/// Events of rendered day with recurring events expanded
+fn day_events(events_str: &str, options: &Options) -> Vec<Event> {
+    for_day(events::deserialize(events_str, options), options)
+}
+
+fn for_day(mut events_vec: Vec<Event>, options: &Options) -> Vec<Event> {
+    events::mask_private(&mut events_vec, options);
+
+    match options::rendered_day(options) {
+        Some(day) => recurrence::expand(events_vec, day, day.add_days(1)),
+        None => events_vec,
+    }
+}
+
 #[wasm_bindgen]
-pub fn render_events(events_str: &str) -> String {
-    // Transform JSON with events to Vec<Event>
-    let events_vec = events::deserialize(events_str);
+pub fn render_events(events_str: &str, options_str: &str) -> String {
+    // Transform JSON with options to Options
+    let options = options::deserialize(options_str);
 
-    // Build matrix with position for each event
-    let matrix = matrix::build(events_vec.clone());
+    // Transform JSON with events to Vec<Event>
+    let events_vec = day_events(events_str, &options);
 
     // Calculate data to render events on grid
-    let positions = positions::calculate(matrix);
+    let positions = positions::layout(events_vec.clone(), &options);
 
     // Render events to SVG
-    svg::render(&events_vec, &positions)
+    svg::render(&events_vec, &positions, &options)
+}
+
+/// Same markup as `render_events` written as UTF-8 straight into bytes,
+/// JS receives `Uint8Array` ready for `Blob` or `Response` without
+/// building a string first
+#[wasm_bindgen]
+pub fn render_events_utf8(events_str: &str, options_str: &str) -> Vec<u8> {
+    let options = options::deserialize(options_str);
+    let events_vec = day_events(events_str, &options);
+    let positions = positions::layout(events_vec.clone(), &options);
+
+    let indent = if options.pretty { Some("  ") } else { None };
+    let mut bytes = vec![];
+    svg::build(&events_vec, &positions, &options, false)
+        .write_io(&mut bytes, indent)
+        .unwrap();
+    bytes
+}
+
+/// Render day view straight into the container without building markup
+#[cfg(feature = "dom")]
+#[wasm_bindgen]
+pub fn render_events_into(
+    container: &dom::Element,
+    events_str: &str,
+    options_str: &str,
+) -> Result<(), JsValue> {
+    let options = options::deserialize(options_str);
+    let events_vec = day_events(events_str, &options);
+    let positions = positions::layout(events_vec.clone(), &options);
+
+    // Same tree as render_events serializes to string
+    let tree = svg::build(&events_vec, &positions, &options, false);
+    dom::render(&tree, container)
+}
+
+/// JSON with operations turning previously rendered day into the new one
+/// and state to pass as `prev_state` next time, empty state renders from scratch
+#[wasm_bindgen]
+pub fn render_patch(prev_state: &str, events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let events_vec = day_events(events_str, &options);
+    let positions = positions::layout(events_vec.clone(), &options);
+
+    // Elements are keyed by event key to find them in DOM
+    let tree = svg::build(&events_vec, &positions, &options, true);
+    serde_json::to_string(&patch::patch(prev_state, &tree)).unwrap()
+}
+
+/// Laid out schedule which keeps state between calls from JS
+#[wasm_bindgen]
+pub struct Schedule {
+    events: Vec<Event>,
+    positions: Vec<EventPosition>,
+    options: Options,
+}
+
+#[wasm_bindgen]
+impl Schedule {
+    #[wasm_bindgen(constructor)]
+    pub fn new(events_str: &str, options_str: &str) -> Schedule {
+        let options = options::deserialize(options_str);
+        let events = day_events(events_str, &options);
+        Schedule::laid_out(events, options)
+    }
+
+    /// Schedule of events read from CSV with `csv_columns` of options
+    pub fn from_csv(csv_str: &str, options_str: &str) -> Result<Schedule, JsValue> {
+        let options = options::deserialize(options_str);
+        let events = csv::deserialize(csv_str, &options).map_err(csv_errors)?;
+        Ok(Schedule::laid_out(for_day(events, &options), options))
+    }
+
+    fn laid_out(events: Vec<Event>, options: Options) -> Schedule {
+        let positions = positions::layout(events.clone(), &options);
+
+        Schedule {
+            events: events,
+            positions: positions,
+            options: options,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        svg::render(&self.events, &self.positions, &self.options)
+    }
+
+    /// Index in the input list of the event under the point of rendered SVG
+    pub fn event_at(&self, x: f32, y: f32) -> Option<u16> {
+        svg::event_at(&self.events, &self.positions, &self.options, x, y)
+            .map(|id| self.events[id as usize].source_id)
+    }
+
+    /// Minute under the vertical coordinate of rendered SVG,
+    /// horizontal coordinate is expected for horizontal orientation
+    pub fn time_at(&self, y: f32, snap: Option<u16>) -> u16 {
+        svg::time_at(y, snap)
+    }
+
+    /// JSON with positions of events affected by moving or resizing
+    /// the event, schedule itself stays unchanged, `null` for unknown id
+    pub fn preview(
+        &self,
+        id: u16,
+        starts_at: Option<u16>,
+        duration: Option<u16>,
+        snap: Option<u16>,
+    ) -> String {
+        // Caller refers to events by their index in the input list
+        let source_id = id;
+        let preview = self
+            .events
+            .iter()
+            .find(|event| event.source_id == source_id)
+            .and_then(|event| {
+                positions::preview(
+                    &self.events,
+                    &self.options,
+                    event.id,
+                    starts_at,
+                    duration,
+                    snap,
+                )
+            })
+            .map(|mut preview| {
+                preview.id = source_id;
+                for pos in &mut preview.positions {
+                    pos.id = self.events[pos.id as usize].source_id;
+                }
+                preview
+            });
+        serde_json::to_string(&preview).unwrap()
+    }
+}
+
+#[wasm_bindgen]
+pub fn render_resources(events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let events_vec = day_events(events_str, &options);
+
+    // Render one column per room or person
+    resources::render(&events_vec, &options)
+}
+
+#[wasm_bindgen]
+pub fn render_heatmap(events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let events_vec = day_events(events_str, &options);
+
+    // Render share of busy people over time, events without resource are skipped
+    heatmap::render(&events_vec, &options)
+}
+
+#[wasm_bindgen]
+pub fn render_agenda(events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let events_vec = day_events(events_str, &options);
+
+    // Render chronological list of events
+    agenda::render(&events_vec, &options)
+}
+
+#[wasm_bindgen]
+pub fn render_month(events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let events_vec = visible_events(events_str, &options);
+
+    // Render month grid with summary for each day
+    month::render(&events_vec, &options)
+}
+
+/// JSON with changes of events between old and new schedule
+#[wasm_bindgen]
+pub fn diff_events(old_events_str: &str, new_events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let old_events = day_events(old_events_str, &options);
+    let new_events = day_events(new_events_str, &options);
+
+    serde_json::to_string(&diff::diff(&old_events, &new_events)).unwrap()
+}
+
+#[wasm_bindgen]
+pub fn render_diff(old_events_str: &str, new_events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let old_events = day_events(old_events_str, &options);
+    let new_events = day_events(new_events_str, &options);
+
+    // Render new schedule on top of outlines of the old one
+    diff::render(&old_events, &new_events, &options)
+}
+
+/// JSON with busy time, free blocks and totals of the rendered day
+#[wasm_bindgen]
+pub fn stats(events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let events_vec = day_events(events_str, &options);
+
+    serde_json::to_string(&stats::calculate(&events_vec, &options)).unwrap()
+}
+
+/// Export events as iCalendar file, `tz` is "UTC", offset like "+02:00",
+/// name like "Europe/Kyiv" written as floating time or empty string for
+/// floating time. `stamp` is creation time from `Date.prototype.toISOString`.
+#[wasm_bindgen]
+pub fn to_ics(events_str: &str, date: &str, tz: &str, stamp: &str) -> Result<String, JsValue> {
+    let events_vec = events::deserialize(events_str, &Options::default());
+    let day = Date::parse(date).ok_or_else(|| JsValue::from_str("Invalid date"))?;
+    let stamp = ics::format_stamp(stamp).ok_or_else(|| JsValue::from_str("Invalid stamp"))?;
+
+    Ok(ics::serialize(&events_vec, day, tz, &stamp))
+}
+
+/// Read events from CSV into JSON accepted by other functions,
+/// errors are reported one per line with line numbers
+#[wasm_bindgen]
+pub fn import_csv(csv_str: &str, options_str: &str) -> Result<String, JsValue> {
+    let options = options::deserialize(options_str);
+
+    let raw_events = csv::read(csv_str, &options.csv_columns).map_err(csv_errors)?;
+    Ok(serde_json::to_string(&raw_events).unwrap())
+}
+
+fn csv_errors(errors: Vec<csv::CsvError>) -> JsValue {
+    let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+    JsValue::from_str(&messages.join("\n"))
+}
+
+#[wasm_bindgen]
+pub fn export_csv(events_str: &str, options_str: &str) -> String {
+    let options = options::deserialize(options_str);
+    let events_vec = events::deserialize(events_str, &options);
+
+    csv::write(&events_vec, &options.csv_columns)
 }
 
 #[cfg(test)]
@@ -50,6 +321,279 @@ mod tests {
           {"title":"6","starts_at":310,"duration":100}
         ]"#;
 
-        println!("{}", render_events(events));
+        println!("{}", render_events(events, "{}"));
+    }
+
+    #[test]
+    fn test_render_events_recurrence() {
+        let events = r#"[
+          {"title":"Standup","starts_at":0,"duration":15,"date":"2026-10-01","rrule":"FREQ=WEEKLY;BYDAY=MO,TH","exdate":["2026-10-19"]},
+          {"title":"Retro","starts_at":60,"duration":60,"date":"2026-10-22"},
+          {"title":"Review","starts_at":60,"duration":60,"date":"2026-10-20"}
+        ]"#;
+
+        let svg = render_events(events, r#"{"date":"2026-10-22"}"#);
+        assert!(svg.contains("Standup"));
+        assert!(svg.contains("Retro"));
+        assert!(!svg.contains("Review"));
+
+        let svg = render_events(events, r#"{"date":"2026-10-19"}"#);
+        assert!(!svg.contains("Standup"));
+
+        // Unsupported rule leaves single event on its date
+        let events = r#"[{"starts_at":0,"duration":30,"date":"2026-10-19","rrule":"FREQ=YEARLY"}]"#;
+        assert!(render_events(events, r#"{"date":"2026-10-19"}"#).contains("role=\"listitem\""));
+        assert!(!render_events(events, r#"{"date":"2027-10-19"}"#).contains("<g clip-path"));
+    }
+
+    #[test]
+    fn test_source_ids() {
+        // Only the last two events are on the rendered day
+        let events = r#"[
+          {"title":"Retro","starts_at":0,"duration":60,"date":"2026-10-20"},
+          {"title":"1","starts_at":60,"duration":60,"date":"2026-10-19"},
+          {"title":"2","starts_at":90,"duration":60,"date":"2026-10-01","rrule":"FREQ=DAILY"}
+        ]"#;
+        let options = r#"{"date":"2026-10-19"}"#;
+        let schedule = Schedule::new(events, options);
+
+        assert_eq!(schedule.event_at(100.0, 80.0), Some(1));
+        assert_eq!(schedule.event_at(400.0, 130.0), Some(2));
+        let preview: serde_json::Value =
+            serde_json::from_str(&schedule.preview(2, Some(300), None, None)).unwrap();
+        assert_eq!(preview["id"], 2);
+        assert_eq!(preview["positions"][0]["id"], 1);
+        assert_eq!(schedule.preview(0, Some(300), None, None), "null");
+
+        let diff: serde_json::Value =
+            serde_json::from_str(&diff_events(events, &events.replace("90", "120"), options))
+                .unwrap();
+        assert_eq!(
+            (&diff[0]["old_id"], &diff[0]["new_id"]),
+            (&serde_json::json!(2), &serde_json::json!(2))
+        );
+    }
+
+    #[test]
+    fn test_render_events_privacy() {
+        let events = r#"[
+          {"title":"Secret","location":"Hidden room","starts_at":60,"duration":60},
+          {"title":"Private","starts_at":70,"duration":30,"private":true}
+        ]"#;
+
+        let svg = render_events(events, r#"{"privacy":true}"#);
+        assert!(!svg.contains("Secret"));
+        assert!(!svg.contains("Hidden room"));
+        assert!(!svg.contains("Private"));
+        assert!(svg.contains("Busy"));
+    }
+
+    #[test]
+    fn test_render_events_data_attributes() {
+        let events = r#"[
+          {"title":"Sync","starts_at":60,"duration":60,"url":"https://meet/1?a&b","ticket":42,"token":"secret"}
+        ]"#;
+
+        let svg = render_events(events, r#"{"data_attributes":["url","ticket"]}"#);
+        assert!(svg.contains("data-url=\"https://meet/1?a&amp;b\""));
+        assert!(svg.contains("data-ticket=\"42\""));
+        assert!(!svg.contains("secret"));
+
+        let svg = render_events(events, r#"{"data_attributes":["url"],"privacy":true}"#);
+        assert!(!svg.contains("data-url"));
+
+        // Extra field can't pose as key of keyed output
+        let events = r#"[{"uid":"a","starts_at":0,"duration":30,"key":"x"}]"#;
+        let patch: serde_json::Value =
+            serde_json::from_str(&render_patch("", events, r#"{"data_attributes":["key"]}"#))
+                .unwrap();
+        let html = patch["ops"][0]["html"].as_str().unwrap();
+        assert_eq!(html.matches("data-key=\"event-uid:a\"").count(), 1);
+        assert!(!html.contains("data-key=\"x\""));
+    }
+
+    #[test]
+    fn test_schedule() {
+        let events = r#"[
+          {"title":"1","starts_at":60,"duration":60},
+          {"title":"2","starts_at":90,"duration":60}
+        ]"#;
+        let schedule = Schedule::new(events, "{}");
+
+        assert!(schedule
+            .render()
+            .contains("aria-label=\"2, 10:30 AM – 11:30 AM\""));
+        assert_eq!(schedule.event_at(400.0, 130.0), Some(1));
+        assert_eq!(schedule.event_at(400.0, 200.0), None);
+        assert_eq!(schedule.time_at(130.0, Some(15)), 135);
+        assert_eq!(
+            schedule.preview(1, Some(200), None, None),
+            r#"{"id":1,"starts_at":200,"duration":60,"positions":[{"id":0,"width_multiplier":1.0,"offset":0,"indent":0},{"id":1,"width_multiplier":1.0,"offset":0,"indent":0}]}"#
+        );
+        assert_eq!(schedule.preview(99, Some(200), None, None), "null");
+    }
+
+    #[test]
+    fn test_schedule_from_csv() {
+        let csv = "title,start,end\nStandup,10:00,10:30\n";
+        let schedule = Schedule::from_csv(csv, "{}").ok().unwrap();
+
+        assert!(schedule.render().contains("Standup"));
+        assert_eq!(schedule.event_at(400.0, 70.0), Some(0));
+    }
+
+    #[test]
+    fn test_render_resources() {
+        let events = r#"[
+          {"title":"Standup","starts_at":0,"duration":15,"resource":"Alice"},
+          {"title":"Review","starts_at":60,"duration":60,"location":"Room 1"},
+          {"title":"1:1","starts_at":70,"duration":30,"resource":"Alice"},
+          {"title":"Lunch","starts_at":180,"duration":60}
+        ]"#;
+
+        let svg = render_resources(events, r#"{"resources":["Room 1"]}"#);
+
+        // Listed resource goes first, events without one end up in "Other"
+        let column = |name: &str| svg.find(&format!("aria-label=\"{}\" role=\"list\"", name));
+        assert!(column("Room 1") < column("Alice"));
+        assert!(column("Alice") < column("Other"));
+        assert!(
+            svg.contains("aria-label=\"Alice\" role=\"list\" transform=\"translate(193.33,0)\"")
+        );
+        assert!(
+            svg.contains("aria-label=\"Other\" role=\"list\" transform=\"translate(386.67,0)\"")
+        );
+        assert_eq!(
+            svg.matches("<rect height=\"60\" width=\"193.33\"/>")
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_exports_keep_private() {
+        let events = r#"[{"uid":"interview","title":"Interview","location":"Room 1","starts_at":0,"duration":30,"private":true}]"#;
+        let options = r#"{"privacy":true}"#;
+
+        assert!(!render_events(events, options).contains("Interview"));
+        let ics = to_ics(events, "2026-10-19", "UTC", "2026-10-19T08:15:00Z").unwrap();
+        assert!(ics.contains("UID:interview\r\n"));
+        assert!(ics.contains("SUMMARY:Interview\r\n"));
+        assert!(ics.contains("LOCATION:Room 1\r\n"));
+        assert!(ics.contains("CLASS:PRIVATE\r\n"));
+        assert_eq!(
+            export_csv(events, options),
+            "title,location,start,end,date\r\nInterview,Room 1,09:00,09:30,\r\n"
+        );
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let events = import_csv("title,start,duration\nStandup,09:00,15\n", "{}").unwrap();
+
+        assert!(render_events(&events, "{}").contains("Standup"));
+        assert_eq!(
+            export_csv(&events, r#"{"csv_columns":{"end":null,"date":null}}"#),
+            "title,location,start,duration\r\nStandup,,09:00,15\r\n"
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let events = r#"[
+          {"title":"Standup","starts_at":0,"duration":15,"category":"meeting"},
+          {"title":"Review","starts_at":60,"duration":60,"category":"meeting","location":"Room 1"}
+        ]"#;
+
+        assert_eq!(
+            stats(events, r#"{"min_focus_time":240}"#),
+            r#"{"busy":75,"context_switches":3,"longest_free":600,"focus_blocks":[{"starts_at":120,"duration":600}],"categories":{"meeting":75},"locations":{"Room 1":60}}"#
+        );
+    }
+
+    #[test]
+    fn test_render_events_utf8() {
+        let events = r#"[{"title":"Café","starts_at":0,"duration":15}]"#;
+        let svg = String::from_utf8(render_events_utf8(events, "{}")).unwrap();
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("Café"));
+        assert_eq!(svg.len(), render_events(events, "{}").len());
+    }
+
+    #[test]
+    fn test_render_pretty() {
+        let events = r#"[{"title":"Standup","starts_at":0,"duration":15}]"#;
+        let svg = render_events(events, r#"{"pretty":true}"#);
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(">\n  <title>"));
+        assert!(svg.contains("Standup"));
+        assert!(!render_events(events, "{}").contains(">\n"));
+    }
+
+    #[test]
+    fn test_render_heatmap() {
+        let events = r#"[
+          {"title":"Standup","starts_at":0,"duration":15,"resource":"Alice"},
+          {"title":"Standup","starts_at":0,"duration":15,"resource":"Bob"},
+          {"title":"1:1","starts_at":70,"duration":30,"resource":"Alice"}
+        ]"#;
+
+        let svg = render_heatmap(events, r##"{"heatmap_colors":["#eee","#000"]}"##);
+        assert!(svg.contains("<title>9 AM – 9:30 AM: 2 of 2 busy</title>"));
+        assert!(svg.contains("fill=\"#eee\""));
+        assert!(!svg.contains("Standup"));
+    }
+
+    #[test]
+    fn test_diff_events() {
+        let old = r#"[
+          {"uid":"a","title":"Standup","starts_at":0,"duration":15},
+          {"uid":"b","title":"Review","starts_at":60,"duration":60}
+        ]"#;
+        let new = r#"[
+          {"uid":"b","title":"Review","starts_at":90,"duration":60}
+        ]"#;
+
+        assert_eq!(
+            diff_events(old, new, "{}"),
+            r#"[{"key":"uid:b","old_id":1,"new_id":0,"changes":["moved"]},{"key":"uid:a","old_id":0,"new_id":null,"changes":["removed"]}]"#
+        );
+    }
+
+    #[test]
+    fn test_render_patch() {
+        let first: serde_json::Value = serde_json::from_str(&render_patch(
+            "",
+            r#"[{"uid":"a","starts_at":0,"duration":30}]"#,
+            "{}",
+        ))
+        .unwrap();
+        assert_eq!(first["ops"].as_array().unwrap().len(), 1);
+        assert_eq!(first["ops"][0]["op"], "create");
+        assert_eq!(first["ops"][0]["key"], "schedule");
+
+        let second: serde_json::Value = serde_json::from_str(&render_patch(
+            first["state"].as_str().unwrap(),
+            r#"[{"uid":"a","starts_at":60,"duration":30}]"#,
+            "{}",
+        ))
+        .unwrap();
+        for op in second["ops"].as_array().unwrap() {
+            assert_eq!(op["op"], "update");
+            assert!(op["key"].as_str().unwrap().ends_with("-uid:a"));
+        }
+
+        let third: serde_json::Value = serde_json::from_str(&render_patch(
+            second["state"].as_str().unwrap(),
+            r#"[{"uid":"b","starts_at":120,"duration":30}]"#,
+            "{}",
+        ))
+        .unwrap();
+        assert!(third["ops"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!({"op":"remove","key":"event-uid:a"})));
     }
 }
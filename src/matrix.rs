@@ -9,10 +9,15 @@ fn get_event_by_id(events: &Vec<Event>, id: u16) -> Option<&Event> {
     None
 }
 
-fn should_create_row(event: &Event, last_row: &Vec<u16>, events: &Vec<Event>) -> bool {
+fn should_create_row(
+    event: &Event,
+    last_row: &Vec<u16>,
+    events: &Vec<Event>,
+    min_duration: u16,
+) -> bool {
     for id in last_row {
         let cur_event = get_event_by_id(events, *id).unwrap();
-        if !event.overlap(cur_event) {
+        if !event.visual_overlap(cur_event, min_duration) {
             return true;
         }
     }
@@ -27,10 +32,15 @@ fn get_two_last_rows(matrix: &mut EventsMatrix) -> (&Vec<u16>, &mut Vec<u16>) {
     (before_last_row, last_row)
 }
 
-pub fn build(mut events: Vec<Event>) -> EventsMatrix {
+/// Order in which events are placed on the grid
+pub fn sort(events: &mut Vec<Event>) {
+    events.sort_by_key(|event| event.starts_at);
+}
+
+pub fn build(mut events: Vec<Event>, min_duration: u16) -> EventsMatrix {
     let mut matrix = EventsMatrix::default();
 
-    events.sort_by_key(|event| event.starts_at);
+    sort(&mut events);
 
     for event in &events {
         if matrix.is_empty() {
@@ -39,7 +49,7 @@ pub fn build(mut events: Vec<Event>) -> EventsMatrix {
         }
 
         let mut last_row_idx = matrix.len() - 1;
-        let create_row = should_create_row(event, &matrix[last_row_idx], &events);
+        let create_row = should_create_row(event, &matrix[last_row_idx], &events, min_duration);
 
         if create_row {
             matrix.push(Vec::<u16>::new());
@@ -56,7 +66,7 @@ pub fn build(mut events: Vec<Event>) -> EventsMatrix {
         for (idx, cur_id) in prev_row.into_iter().enumerate() {
             let cur_event = get_event_by_id(&events, *cur_id).unwrap();
 
-            if event.overlap(cur_event) {
+            if event.visual_overlap(cur_event, min_duration) {
                 let row_len = row_to_push.len();
                 if row_len == 0 || row_len - 1 < idx {
                     row_to_push.push(cur_event.id);
@@ -78,147 +88,199 @@ mod tests {
     #[test]
     fn test_build() {
         assert_eq!(
-            build(vec![
-                Event {
-                    id: 0,
-                    starts_at: 120,
-                    duration: 45,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 1,
-                    starts_at: 240,
-                    duration: 60,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 2,
-                    starts_at: 75,
-                    duration: 60,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 3,
-                    starts_at: 360,
-                    duration: 25,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 4,
-                    starts_at: 420,
-                    duration: 120,
-                    title: None,
-                    location: None,
-                },
-            ]),
+            build(
+                vec![
+                    Event {
+                        id: 0,
+                        starts_at: 120,
+                        duration: 45,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 1,
+                        starts_at: 240,
+                        duration: 60,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 2,
+                        starts_at: 75,
+                        duration: 60,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 3,
+                        starts_at: 360,
+                        duration: 25,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 4,
+                        starts_at: 420,
+                        duration: 120,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                ],
+                0
+            ),
             vec![vec![2, 0], vec![1], vec![3], vec![4]]
         );
 
         assert_eq!(
-            build(vec![
-                Event {
-                    id: 0,
-                    starts_at: 120,
-                    duration: 45,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 1,
-                    starts_at: 240,
-                    duration: 160,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 2,
-                    starts_at: 75,
-                    duration: 60,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 3,
-                    starts_at: 360,
-                    duration: 25,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 4,
-                    starts_at: 420,
-                    duration: 120,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 5,
-                    starts_at: 90,
-                    duration: 280,
-                    title: None,
-                    location: None,
-                },
-            ]),
+            build(
+                vec![
+                    Event {
+                        id: 0,
+                        starts_at: 120,
+                        duration: 45,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 1,
+                        starts_at: 240,
+                        duration: 160,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 2,
+                        starts_at: 75,
+                        duration: 60,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 3,
+                        starts_at: 360,
+                        duration: 25,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 4,
+                        starts_at: 420,
+                        duration: 120,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 5,
+                        starts_at: 90,
+                        duration: 280,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                ],
+                0
+            ),
             vec![vec![2, 5, 0], vec![1, 5, 3], vec![4]]
         );
 
         assert_eq!(
-            build(vec![
-                Event {
-                    id: 0,
-                    starts_at: 60,
-                    duration: 60,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 1,
-                    starts_at: 70,
-                    duration: 30,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 2,
-                    starts_at: 110,
-                    duration: 120,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 3,
-                    starts_at: 110,
-                    duration: 30,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 4,
-                    starts_at: 200,
-                    duration: 30,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 5,
-                    starts_at: 220,
-                    duration: 100,
-                    title: None,
-                    location: None,
-                },
-                Event {
-                    id: 6,
-                    starts_at: 310,
-                    duration: 100,
-                    title: None,
-                    location: None,
-                },
-            ]),
+            build(
+                vec![
+                    Event {
+                        id: 0,
+                        starts_at: 60,
+                        duration: 60,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 1,
+                        starts_at: 70,
+                        duration: 30,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 2,
+                        starts_at: 110,
+                        duration: 120,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 3,
+                        starts_at: 110,
+                        duration: 30,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 4,
+                        starts_at: 200,
+                        duration: 30,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 5,
+                        starts_at: 220,
+                        duration: 100,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                    Event {
+                        id: 6,
+                        starts_at: 310,
+                        duration: 100,
+                        title: None,
+                        location: None,
+                        ..Default::default()
+                    },
+                ],
+                0
+            ),
             vec![vec![0, 1], vec![0, 2, 3], vec![4, 2, 5], vec![6, 5]]
         );
     }
+
+    #[test]
+    fn test_build_min_duration() {
+        let events = vec![
+            Event {
+                id: 0,
+                starts_at: 60,
+                duration: 5,
+                title: None,
+                location: None,
+                ..Default::default()
+            },
+            Event {
+                id: 1,
+                starts_at: 70,
+                duration: 30,
+                title: None,
+                location: None,
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(build(events.clone(), 0), vec![vec![0], vec![1]]);
+        assert_eq!(build(events, 20), vec![vec![0, 1]]);
+    }
 }
@@ -0,0 +1,263 @@
+use date::{days_in_month, Date};
+use matrix;
+use options;
+use recurrence;
+use svg;
+use svg::EventRect;
+use types::{Event, HTMLElement, Options};
+
+const WEEKDAYS: [&'static str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const HEADER_HEIGHT: f32 = 20.0;
+const CELL_HEIGHT: f32 = 100.0;
+const DAY_LABEL_HEIGHT: f32 = 22.0;
+const LINE_HEIGHT: f32 = 14.0;
+lazy_static! {
+    static ref CELL_WIDTH: f32 = svg::SVG_WIDTH as f32 / WEEKDAYS.len() as f32;
+}
+
+/// First date shown in the grid of month and number of weeks
+pub fn grid_range(month: Date) -> (Date, u8) {
+    let first = month.first_of_month();
+    let leading_days = first.weekday() as u16;
+    let days = leading_days + days_in_month(first.year, first.month) as u16;
+    let weeks = days.div_ceil(7).max(5);
+
+    (first.add_days(-(leading_days as i32)), weeks as u8)
+}
+
+/// Events of the day ordered the same way as on the day grid
+pub fn day_events(events: &Vec<Event>, day: Date) -> Vec<Event> {
+    let mut day_events: Vec<Event> = events
+        .iter()
+        .filter(|event| event.date == Some(day))
+        .cloned()
+        .collect();
+    matrix::sort(&mut day_events);
+    day_events
+}
+
+/// Lines fitting into the cell and number of events left out
+pub fn summarize(events: &Vec<Event>, lines: usize) -> (Vec<String>, usize) {
+    let shown = if events.len() <= lines {
+        events.len()
+    } else {
+        // Last line is taken by "+N more"
+        lines.saturating_sub(1)
+    };
+    let summaries = events[..shown]
+        .iter()
+        .map(|event| {
+            let starts_at = svg::format_interval(event.label_range().0);
+            match svg::event_label(event) {
+                Some(label) => format!("{} {}", starts_at, label),
+                None => starts_at,
+            }
+        })
+        .collect();
+
+    (summaries, events.len() - shown)
+}
+
+fn create_weekday_label<'a>(idx: usize, weekday: &'a str) -> HTMLElement<'a> {
+    let x = (idx as f32 + 0.5) * *CELL_WIDTH;
+    let mut text = HTMLElement::new("text")
+        .text_anchor("middle")
+        .x(x)
+        .y(HEADER_HEIGHT / 2.0)
+        .dy(".35em")
+        .fill("#c9c9c9");
+    text.append_child(weekday);
+    text
+}
+
+fn create_line<'a>(text: String, line: usize, fill: &str) -> HTMLElement<'a> {
+    let y = DAY_LABEL_HEIGHT + (line as f32 + 0.5) * LINE_HEIGHT;
+    let mut el = HTMLElement::new("text").x(6).y(y).dy(".35em").fill(fill);
+    el.append_child(text);
+    el
+}
+
+/// Cell of the grid, without day it is left blank
+fn create_day_cell<'a>(
+    day: Option<Date>,
+    idx: usize,
+    in_month: bool,
+    events: &Vec<Event>,
+    clip_path_id: &str,
+) -> HTMLElement<'a> {
+    let x = (idx % WEEKDAYS.len()) as f32 * *CELL_WIDTH;
+    let y = HEADER_HEIGHT + (idx / WEEKDAYS.len()) as f32 * CELL_HEIGHT;
+    let mut g = HTMLElement::new("g")
+        .translate(x, y)
+        .clip_path(&format!("url(#{})", clip_path_id));
+
+    let background = if in_month { "none" } else { "#fcfcfc" };
+    g.append_child(
+        HTMLElement::new("rect")
+            .width(*CELL_WIDTH)
+            .height(CELL_HEIGHT)
+            .fill(background)
+            .stroke("#f0f0f0"),
+    );
+
+    let mut label = HTMLElement::new("text")
+        .x(6)
+        .y(DAY_LABEL_HEIGHT / 2.0)
+        .dy(".35em")
+        .fill(if in_month { "#919191" } else { "#c9c9c9" });
+    let day = match day {
+        Some(day) => day,
+        None => return g,
+    };
+    label.append_child(day.day.to_string());
+    g.append_child(label);
+
+    let lines = ((CELL_HEIGHT - DAY_LABEL_HEIGHT) / LINE_HEIGHT) as usize;
+    let (summaries, hidden) = summarize(&day_events(events, day), lines);
+    let summaries_count = summaries.len();
+    for (line, summary) in summaries.into_iter().enumerate() {
+        g.append_child(create_line(summary, line, "#45a51c"));
+    }
+    if hidden > 0 {
+        let more = format!("+{} more", hidden);
+        g.append_child(create_line(more, summaries_count, "#919191"));
+    }
+
+    g
+}
+
+pub fn render(events: &Vec<Event>, options: &Options) -> String {
+    // Without any date there is nothing to place, grid stays blank
+    let month = options::rendered_day(options)
+        .or_else(|| events.iter().filter_map(|event| event.date).min());
+    let (start, weeks) = match month {
+        Some(month) => {
+            let (start, weeks) = grid_range(month);
+            (Some(start), weeks)
+        }
+        None => (None, 5),
+    };
+    let events = &match start {
+        Some(start) => {
+            let end = start.add_days(weeks as i32 * WEEKDAYS.len() as i32);
+            recurrence::expand(events.clone(), start, end)
+        }
+        None => vec![],
+    };
+    let height = HEADER_HEIGHT + weeks as f32 * CELL_HEIGHT;
+
+    let mut svg = HTMLElement::new("svg")
+        .xmlns(svg::SVG_NS)
+        .width(svg::SVG_WIDTH)
+        .height(height)
+        .view_box(svg::SVG_WIDTH, height)
+        .style("font-family: Helvetica, Arial; font-size: 0.8em;");
+
+    let mut header = HTMLElement::new("g");
+    for (idx, weekday) in WEEKDAYS.iter().enumerate() {
+        header.append_child(create_weekday_label(idx, weekday));
+    }
+    svg.append_child(header);
+
+    // All cells have the same size so they share clip path
+    let (clip_path_id, clip_path) = svg::create_clip_path(&EventRect {
+        x: 0.0,
+        y: 0.0,
+        width: *CELL_WIDTH,
+        height: CELL_HEIGHT,
+    });
+    svg.append_child(clip_path);
+
+    let mut cells = HTMLElement::new("g");
+    for idx in 0..weeks as usize * WEEKDAYS.len() {
+        let day = start.map(|start| start.add_days(idx as i32));
+        let in_month = match (day, month) {
+            (Some(day), Some(month)) => day.month == month.month,
+            _ => true,
+        };
+        cells.append_child(create_day_cell(day, idx, in_month, events, &clip_path_id));
+    }
+    svg.append_child(cells);
+
+    svg::markup(&svg, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: u16, starts_at: u16, title: &str) -> Event {
+        Event {
+            id: id,
+            starts_at: starts_at,
+            duration: 30,
+            title: Some(title.to_string()),
+            date: Some(Date::new(2026, 10, 19)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_grid_range() {
+        assert_eq!(
+            grid_range(Date::new(2026, 10, 19)),
+            (Date::new(2026, 9, 28), 5)
+        );
+        assert_eq!(
+            grid_range(Date::new(2026, 3, 1)),
+            (Date::new(2026, 2, 23), 6)
+        );
+        // February 2027 fits into 4 weeks but grid keeps at least 5
+        assert_eq!(
+            grid_range(Date::new(2027, 2, 1)),
+            (Date::new(2027, 2, 1), 5)
+        );
+    }
+
+    #[test]
+    fn test_day_events() {
+        let mut other_day = event(2, 0, "Other day");
+        other_day.date = Some(Date::new(2026, 10, 20));
+        let events = vec![event(0, 120, "Lunch"), event(1, 0, "Standup"), other_day];
+
+        let ids: Vec<u16> = day_events(&events, Date::new(2026, 10, 19))
+            .iter()
+            .map(|event| event.id)
+            .collect();
+        assert_eq!(ids, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let events = vec![
+            event(0, 0, "Standup"),
+            event(1, 90, "Review"),
+            event(2, 120, "Lunch"),
+        ];
+
+        assert_eq!(
+            summarize(&events, 3),
+            (
+                vec![
+                    "9 AM Standup".to_string(),
+                    "10:30 AM Review".to_string(),
+                    "11 AM Lunch".to_string(),
+                ],
+                0
+            )
+        );
+        assert_eq!(summarize(&events, 2), (vec!["9 AM Standup".to_string()], 2));
+    }
+
+    #[test]
+    fn test_render_without_date() {
+        let mut undated = event(0, 0, "Standup");
+        undated.date = None;
+        let svg = render(&vec![undated], &Options::default());
+
+        assert!(svg.contains(">Mon</text>"));
+        assert_eq!(svg.matches("<g clip-path").count(), 35);
+        assert!(!svg.contains("Standup"));
+        assert!(render(&vec![], &Options::default()).starts_with("<svg"));
+    }
+}
@@ -0,0 +1,71 @@
+extern crate serde_json;
+
+use date::{self, Date};
+use svg;
+use types::{Now, Options};
+
+pub fn deserialize(options_json: &str) -> Options {
+    serde_json::from_str(options_json).unwrap()
+}
+
+/// Date of rendered day, invalid date is ignored
+pub fn rendered_day(options: &Options) -> Option<Date> {
+    options.date.as_ref().and_then(|date| Date::parse(date))
+}
+
+/// Current time in minutes since start of the grid,
+/// timestamps from other days are shifted by whole days
+pub fn now(options: &Options) -> Option<i32> {
+    let timestamp = match options.now {
+        Some(Now::Minutes(minutes)) => return Some(minutes as i32),
+        Some(Now::Timestamp(ref timestamp)) => timestamp,
+        None => return None,
+    };
+
+    let (day, time) = match timestamp.find('T') {
+        Some(idx) => (Date::parse(&timestamp[..idx]), &timestamp[idx + 1..]),
+        None => (None, &timestamp[..]),
+    };
+    let minutes = date::parse_time(time)? as i32 - svg::FIRST_HOUR as i32 * 60;
+    match (day, rendered_day(options)) {
+        (Some(day), Some(rendered_day)) => {
+            Some(minutes + (day.days() - rendered_day.days()) * 24 * 60)
+        }
+        _ => Some(minutes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_now() {
+        assert_eq!(now(&deserialize("{}")), None);
+        assert_eq!(now(&deserialize(r#"{"now":90}"#)), Some(90));
+        assert_eq!(now(&deserialize(r#"{"now":"10:30"}"#)), Some(90));
+        assert_eq!(
+            now(&deserialize(r#"{"now":"2026-10-19T08:00"}"#)),
+            Some(-60)
+        );
+        assert_eq!(
+            now(&deserialize(
+                r#"{"now":"2026-10-20T09:00:00","date":"2026-10-19"}"#
+            )),
+            Some(1440)
+        );
+        // Time zone of the grid is unknown so UTC can't be placed on it
+        assert_eq!(now(&deserialize(r#"{"now":"2026-10-19T09:00:00Z"}"#)), None);
+        assert_eq!(
+            now(&deserialize(r#"{"now":"2026-10-19T09:00+02:00"}"#)),
+            None
+        );
+        assert_eq!(now(&deserialize(r#"{"now":"later"}"#)), None);
+        assert_eq!(
+            now(&deserialize(
+                r#"{"now":"2026-10-19T10:00","date":"2026-13-45"}"#
+            )),
+            Some(60)
+        );
+    }
+}
@@ -0,0 +1,322 @@
+extern crate serde_json;
+
+use std::collections::BTreeMap;
+use types::{HTMLElement, KeyedNode, Node, Patch, PatchOp};
+
+type State = BTreeMap<String, KeyedNode>;
+
+/// Keyed elements of the tree, children of elements are tracked by key
+/// only when all of them are keyed
+fn collect<'t, 'a>(
+    el: &'t HTMLElement<'a>,
+    parent: Option<&String>,
+    state: &mut State,
+    elements: &mut BTreeMap<String, &'t HTMLElement<'a>>,
+) {
+    let key = match el.key() {
+        Some(key) => key,
+        None => return,
+    };
+
+    let keyed_children: Vec<&HTMLElement> = el
+        .children()
+        .iter()
+        .filter_map(|node| match node {
+            Node::Element(child) if child.key().is_some() => Some(child),
+            _ => None,
+        })
+        .collect();
+    let all_keyed = !keyed_children.is_empty() && keyed_children.len() == el.children().len();
+
+    state.insert(
+        key.to_owned(),
+        KeyedNode {
+            parent: parent.cloned(),
+            attrs: el.attributes().clone(),
+            html: if all_keyed {
+                None
+            } else {
+                Some(el.inner_html())
+            },
+            children: if all_keyed {
+                keyed_children
+                    .iter()
+                    .map(|child| child.key().unwrap().to_owned())
+                    .collect()
+            } else {
+                vec![]
+            },
+        },
+    );
+    elements.insert(key.to_owned(), el);
+
+    if all_keyed {
+        for child in keyed_children {
+            collect(child, Some(key), state, elements);
+        }
+    }
+}
+
+struct Reconciler<'s, 't, 'a: 't> {
+    old: &'s State,
+    new: &'s State,
+    elements: &'s BTreeMap<String, &'t HTMLElement<'a>>,
+    ops: Vec<PatchOp>,
+}
+impl<'s, 't, 'a> Reconciler<'s, 't, 'a> {
+    fn update(&mut self, key: &String) {
+        let (old_node, new_node) = (&self.old[key], &self.new[key]);
+
+        let attrs: BTreeMap<String, String> = new_node
+            .attrs
+            .iter()
+            .filter(|(name, value)| old_node.attrs.get(*name) != Some(value))
+            .map(|(name, value)| (name.to_owned(), value.to_owned()))
+            .collect();
+        let removed_attrs: Vec<String> = old_node
+            .attrs
+            .keys()
+            .filter(|name| !new_node.attrs.contains_key(*name))
+            .cloned()
+            .collect();
+        let html = match (&old_node.html, &new_node.html) {
+            (old_html, Some(html)) if old_html.as_ref() != Some(html) => Some(html.to_owned()),
+            // Children become keyed, they are created with markup
+            (Some(_), None) => Some(self.elements[key].inner_html()),
+            _ => None,
+        };
+
+        let children_keyed = old_node.html.is_none() && new_node.html.is_none();
+        if !attrs.is_empty() || !removed_attrs.is_empty() || html.is_some() {
+            self.ops.push(PatchOp::Update {
+                key: key.to_owned(),
+                attrs: attrs,
+                removed_attrs: removed_attrs,
+                html: html,
+            });
+        }
+        if children_keyed {
+            self.update_children(key);
+        }
+    }
+
+    /// Children are placed from the last one so the next sibling is
+    /// always in place already
+    fn update_children(&mut self, parent: &String) {
+        let (old, new) = (self.old, self.new);
+        let mut live: Vec<&String> = old[parent]
+            .children
+            .iter()
+            .filter(|key| new.get(*key).map(|node| node.parent.as_ref()) == Some(Some(parent)))
+            .collect();
+
+        let mut next: Option<&String> = None;
+        for key in new[parent].children.iter().rev() {
+            match live.iter().position(|live_key| *live_key == key) {
+                Some(idx) => {
+                    if live.get(idx + 1).cloned() != next {
+                        self.ops.push(PatchOp::Move {
+                            key: key.to_owned(),
+                            parent: Some(parent.to_owned()),
+                            before: next.cloned(),
+                        });
+                        live.remove(idx);
+                        insert_before(&mut live, key, next);
+                    }
+                    self.update(key);
+                }
+                None => {
+                    self.ops.push(PatchOp::Create {
+                        key: key.to_owned(),
+                        parent: Some(parent.to_owned()),
+                        before: next.cloned(),
+                        html: self.elements[key].to_string(),
+                    });
+                    insert_before(&mut live, key, next);
+                }
+            }
+            next = Some(key);
+        }
+    }
+}
+
+fn insert_before<'k>(live: &mut Vec<&'k String>, key: &'k String, next: Option<&String>) {
+    let idx = match next {
+        Some(next) => live
+            .iter()
+            .position(|live_key| *live_key == next)
+            .unwrap_or(live.len()),
+        None => live.len(),
+    };
+    live.insert(idx, key);
+}
+
+/// Operations turning elements rendered with `prev_state` into the tree,
+/// empty or unreadable state means everything is created again.
+/// Root has to be keyed since it's the target of the first `Create`.
+pub fn patch(prev_state: &str, root: &HTMLElement) -> Patch {
+    let old: State = serde_json::from_str(prev_state).unwrap_or_else(|_| State::new());
+    let mut new = State::new();
+    let mut elements = BTreeMap::new();
+    collect(root, None, &mut new, &mut elements);
+
+    let mut reconciler = Reconciler {
+        old: &old,
+        new: &new,
+        elements: &elements,
+        ops: vec![],
+    };
+
+    // Elements inside of removed ones go away together with them
+    for (key, node) in &old {
+        let moved_away = match new.get(key) {
+            Some(new_node) => new_node.parent != node.parent,
+            None => true,
+        };
+        let parent_kept = match node.parent {
+            Some(ref parent) => new.get(parent).is_some_and(|parent| parent.html.is_none()),
+            None => true,
+        };
+        if moved_away && parent_kept {
+            reconciler.ops.push(PatchOp::Remove {
+                key: key.to_owned(),
+            });
+        }
+    }
+
+    let root_key = root.key().expect("root of patched tree needs a key");
+    if old.contains_key(root_key) {
+        reconciler.update(root_key);
+    } else {
+        reconciler.ops.push(PatchOp::Create {
+            key: root_key.to_owned(),
+            parent: None,
+            before: None,
+            html: root.to_string(),
+        });
+    }
+
+    Patch {
+        state: serde_json::to_string(&new).unwrap(),
+        ops: reconciler.ops,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyed<'a>(tag: &'a str, key: &str, fill: &str) -> HTMLElement<'a> {
+        let mut el = HTMLElement::new(tag).fill(fill);
+        el.set_key(key.to_string());
+        el
+    }
+
+    fn tree<'a>(children: &[(&str, &str)]) -> HTMLElement<'a> {
+        let mut root = keyed("svg", "root", "none");
+        for (key, fill) in children {
+            let mut child = keyed("g", key, fill);
+            child.append_child(key.to_uppercase());
+            root.append_child(child);
+        }
+        root
+    }
+
+    #[test]
+    fn test_patch_create() {
+        let patch = patch("", &tree(&[("a", "red")]));
+
+        assert_eq!(
+            patch.ops,
+            vec![PatchOp::Create {
+                key: "root".to_string(),
+                parent: None,
+                before: None,
+                html: "<svg data-key=\"root\" fill=\"none\">\
+                       <g data-key=\"a\" fill=\"red\">A</g></svg>"
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_patch_invalid_state() {
+        let first = patch("", &tree(&[("a", "red")]));
+
+        for state in &["{\"root\":", "[]"] {
+            let second = patch(state, &tree(&[("a", "red")]));
+            assert_eq!(second.ops, first.ops);
+            assert_eq!(second.state, first.state);
+        }
+    }
+
+    #[test]
+    fn test_patch_unchanged() {
+        let first = patch("", &tree(&[("a", "red"), ("b", "red")]));
+        let second = patch(&first.state, &tree(&[("a", "red"), ("b", "red")]));
+
+        assert_eq!(second.ops, vec![]);
+        assert_eq!(second.state, first.state);
+    }
+
+    #[test]
+    fn test_patch_keyed_children() {
+        let first = patch("", &tree(&[("a", "red"), ("b", "red"), ("c", "red")]));
+        let new_tree = tree(&[("c", "red"), ("a", "blue"), ("d", "red")]).width(600);
+
+        assert_eq!(
+            patch(&first.state, &new_tree).ops,
+            vec![
+                PatchOp::Remove {
+                    key: "b".to_string(),
+                },
+                PatchOp::Update {
+                    key: "root".to_string(),
+                    attrs: vec![("width".to_string(), "600".to_string())]
+                        .into_iter()
+                        .collect(),
+                    removed_attrs: vec![],
+                    html: None,
+                },
+                PatchOp::Create {
+                    key: "d".to_string(),
+                    parent: Some("root".to_string()),
+                    before: None,
+                    html: "<g data-key=\"d\" fill=\"red\">D</g>".to_string(),
+                },
+                PatchOp::Move {
+                    key: "a".to_string(),
+                    parent: Some("root".to_string()),
+                    before: Some("d".to_string()),
+                },
+                PatchOp::Update {
+                    key: "a".to_string(),
+                    attrs: vec![("fill".to_string(), "blue".to_string())]
+                        .into_iter()
+                        .collect(),
+                    removed_attrs: vec![],
+                    html: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patch_html() {
+        let first = patch("", &tree(&[("a", "red")]));
+        let mut new_tree = keyed("svg", "root", "none");
+        let mut a = keyed("g", "a", "red");
+        a.append_child("Changed");
+        new_tree.append_child(a);
+
+        assert_eq!(
+            patch(&first.state, &new_tree).ops,
+            vec![PatchOp::Update {
+                key: "a".to_string(),
+                attrs: BTreeMap::new(),
+                removed_attrs: vec![],
+                html: Some("Changed".to_string()),
+            }]
+        );
+    }
+}
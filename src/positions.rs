@@ -1,4 +1,46 @@
-use types::{EventPosition, EventsMatrix};
+use matrix;
+use svg;
+use types::{Event, EventPosition, EventsMatrix, Layout, Options, Preview, Status};
+
+/// Deeper stacks of cascade share the last indent
+pub const MAX_CASCADE_INDENT: u8 = 20;
+
+/// Cancelled events may be drawn below others with full width
+pub fn takes_space(event: &Event, options: &Options) -> bool {
+    options.layout_cancelled || event.status != Status::Cancelled
+}
+
+/// Calculate positions of events with layout picked in options
+pub fn layout(events: Vec<Event>, options: &Options) -> Vec<EventPosition> {
+    let min_duration = svg::min_event_duration(options);
+
+    let (events, cancelled): (Vec<Event>, Vec<Event>) = events
+        .into_iter()
+        .partition(|event| takes_space(event, options));
+
+    let mut positions = match options.layout {
+        Layout::Columns => {
+            // Build matrix with position for each event
+            let matrix = matrix::build(events, min_duration);
+
+            // Calculate data to render events on grid
+            calculate(matrix)
+        }
+        // Stack overlapping events on top of each other
+        Layout::Cascade => calculate_cascade(events, min_duration),
+    };
+
+    for event in cancelled {
+        positions.push(EventPosition {
+            id: event.id,
+            width_multiplier: 1.0,
+            offset: 0,
+            indent: 0,
+        });
+    }
+
+    positions
+}
 
 pub fn calculate(matrix: EventsMatrix) -> Vec<EventPosition> {
     let mut positions = vec![];
@@ -34,6 +76,7 @@ pub fn calculate(matrix: EventsMatrix) -> Vec<EventPosition> {
                 id: *event,
                 width_multiplier: 1.0 / width_multiplier as f32,
                 offset: offset as u8,
+                indent: 0,
             });
             calculated_events.push(*event);
         }
@@ -42,6 +85,112 @@ pub fn calculate(matrix: EventsMatrix) -> Vec<EventPosition> {
     positions
 }
 
+/// IDs of events connected to the event through overlapping
+pub fn cluster(events: &Vec<Event>, id: u16, min_duration: u16) -> Vec<u16> {
+    let mut ids = vec![id];
+    let mut idx = 0;
+
+    while idx < ids.len() {
+        let current = events.iter().find(|event| event.id == ids[idx]).unwrap();
+        for event in events {
+            if !ids.contains(&event.id) && event.visual_overlap(current, min_duration) {
+                ids.push(event.id);
+            }
+        }
+        idx += 1;
+    }
+
+    ids
+}
+
+fn snap_to(value: u16, snap: Option<u16>) -> u16 {
+    match snap {
+        // Values close to the limit are rounded down instead of overflowing
+        Some(snap) if snap > 0 => value.saturating_add(snap / 2) / snap * snap,
+        _ => value,
+    }
+}
+
+/// Lay out events as if the event was moved or resized, only positions
+/// of its cluster before and after the change are returned,
+/// None when there is no event with the id
+pub fn preview(
+    events: &Vec<Event>,
+    options: &Options,
+    id: u16,
+    starts_at: Option<u16>,
+    duration: Option<u16>,
+    snap: Option<u16>,
+) -> Option<Preview> {
+    let min_duration = svg::min_event_duration(options);
+    let grid_duration = svg::grid_duration();
+    let mut changed_events = events.clone();
+    {
+        let event = changed_events.iter_mut().find(|event| event.id == id)?;
+
+        // Keep event inside of visible window
+        let min_size = match snap {
+            Some(snap) if snap > 0 => snap,
+            _ => 1,
+        };
+        let new_duration = snap_to(duration.unwrap_or(event.duration), snap)
+            .max(min_size)
+            .min(grid_duration);
+        let new_starts_at =
+            snap_to(starts_at.unwrap_or(event.starts_at), snap).min(grid_duration - new_duration);
+
+        event.starts_at = new_starts_at;
+        event.duration = new_duration;
+        event.label_time = None;
+    }
+
+    let mut ids = cluster(events, id, min_duration);
+    for cluster_id in cluster(&changed_events, id, min_duration) {
+        if !ids.contains(&cluster_id) {
+            ids.push(cluster_id);
+        }
+    }
+
+    let event = changed_events.iter().find(|event| event.id == id)?;
+    Some(Preview {
+        id: id,
+        starts_at: event.starts_at,
+        duration: event.duration,
+        positions: layout(changed_events.clone(), options)
+            .into_iter()
+            .filter(|pos| ids.contains(&pos.id))
+            .collect(),
+    })
+}
+
+/// Every event takes full width and is indented by its overlap depth,
+/// positions are ordered by start time to keep z-order
+pub fn calculate_cascade(mut events: Vec<Event>, min_duration: u16) -> Vec<EventPosition> {
+    let mut positions = Vec::<EventPosition>::new();
+
+    events.sort_by_key(|event| event.starts_at);
+
+    for (idx, event) in events.iter().enumerate() {
+        let indent = events[..idx]
+            .iter()
+            .zip(&positions)
+            .filter(|(prev_event, _)| event.visual_overlap(prev_event, min_duration))
+            .map(|(_, prev_pos)| (prev_pos.indent + 1).min(MAX_CASCADE_INDENT))
+            .max()
+            .unwrap_or(0);
+
+        positions.push(EventPosition {
+            id: event.id,
+            width_multiplier: 1.0,
+            offset: 0,
+            indent: indent,
+        });
+    }
+
+    positions
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -54,31 +203,37 @@ mod tests {
                     id: 0,
                     width_multiplier: 1.0 / 3.0,
                     offset: 0,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 1,
                     width_multiplier: 1.0 / 3.0,
                     offset: 1,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 2,
                     width_multiplier: 1.0 / 3.0,
                     offset: 0,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 3,
                     width_multiplier: 1.0 / 3.0,
                     offset: 2,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 4,
                     width_multiplier: 1.0 / 3.0,
                     offset: 2,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 5,
                     width_multiplier: 1.0,
                     offset: 0,
+                    indent: 0,
                 },
             ]
         );
@@ -90,38 +245,211 @@ mod tests {
                     id: 0,
                     width_multiplier: 1.,
                     offset: 0,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 1,
                     width_multiplier: 0.25,
                     offset: 0,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 2,
                     width_multiplier: 0.25,
                     offset: 1,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 3,
                     width_multiplier: 0.25,
                     offset: 0,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 4,
                     width_multiplier: 0.25,
                     offset: 2,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 5,
                     width_multiplier: 0.25,
                     offset: 3,
+                    indent: 0,
                 },
                 EventPosition {
                     id: 6,
                     width_multiplier: 1.0,
                     offset: 0,
+                    indent: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_calculate_cascade() {
+        let event = |id, starts_at, duration| Event {
+            id: id,
+            starts_at: starts_at,
+            duration: duration,
+            title: None,
+            location: None,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            calculate_cascade(
+                vec![
+                    event(0, 120, 60),
+                    event(1, 60, 90),
+                    event(2, 130, 20),
+                    event(3, 200, 30),
+                    event(4, 225, 5),
+                ],
+                10
+            ),
+            &[
+                EventPosition {
+                    id: 1,
+                    width_multiplier: 1.0,
+                    offset: 0,
+                    indent: 0,
+                },
+                EventPosition {
+                    id: 0,
+                    width_multiplier: 1.0,
+                    offset: 0,
+                    indent: 1,
+                },
+                EventPosition {
+                    id: 2,
+                    width_multiplier: 1.0,
+                    offset: 0,
+                    indent: 2,
+                },
+                EventPosition {
+                    id: 3,
+                    width_multiplier: 1.0,
+                    offset: 0,
+                    indent: 0,
+                },
+                EventPosition {
+                    id: 4,
+                    width_multiplier: 1.0,
+                    offset: 0,
+                    indent: 1,
                 },
             ]
         );
     }
+
+    #[test]
+    fn test_calculate_cascade_deep_stack() {
+        let events = (0..300)
+            .map(|id| Event {
+                id: id,
+                starts_at: id,
+                duration: 600,
+                ..Default::default()
+            })
+            .collect();
+        let positions = calculate_cascade(events, 10);
+
+        assert_eq!(positions[1].indent, 1);
+        assert_eq!(positions[299].indent, MAX_CASCADE_INDENT);
+    }
+
+    fn event(id: u16, starts_at: u16, duration: u16) -> Event {
+        Event {
+            id: id,
+            starts_at: starts_at,
+            duration: duration,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_layout_cancelled() {
+        let mut events = vec![event(0, 0, 60), event(1, 30, 60)];
+        events[1].status = Status::Cancelled;
+
+        let widths = |options: &Options| -> Vec<(u16, f32)> {
+            layout(events.clone(), options)
+                .iter()
+                .map(|pos| (pos.id, pos.width_multiplier))
+                .collect()
+        };
+
+        assert_eq!(widths(&Options::default()), vec![(0, 0.5), (1, 0.5)]);
+        assert_eq!(
+            widths(&Options {
+                layout_cancelled: false,
+                ..Default::default()
+            }),
+            vec![(0, 1.0), (1, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_cluster() {
+        let events = vec![
+            event(0, 0, 60),
+            event(1, 30, 60),
+            event(2, 80, 30),
+            event(3, 110, 30),
+            event(4, 150, 10),
+        ];
+
+        assert_eq!(cluster(&events, 0, 0), vec![0, 1, 2]);
+        assert_eq!(cluster(&events, 3, 0), vec![3]);
+        assert_eq!(cluster(&events, 3, 45), vec![3, 2, 4, 1, 0]);
+    }
+
+    #[test]
+    fn test_preview() {
+        let events = vec![event(0, 0, 60), event(1, 30, 60), event(2, 200, 60)];
+        let options = Options::default();
+
+        // Event 1 moves from the cluster of event 0 to the cluster of event 2
+        assert_eq!(
+            preview(&events, &options, 1, Some(223), None, Some(15)),
+            Some(Preview {
+                id: 1,
+                starts_at: 225,
+                duration: 60,
+                positions: vec![
+                    EventPosition {
+                        id: 0,
+                        width_multiplier: 1.0,
+                        offset: 0,
+                        indent: 0,
+                    },
+                    EventPosition {
+                        id: 2,
+                        width_multiplier: 0.5,
+                        offset: 0,
+                        indent: 0,
+                    },
+                    EventPosition {
+                        id: 1,
+                        width_multiplier: 0.5,
+                        offset: 1,
+                        indent: 0,
+                    },
+                ],
+            })
+        );
+
+        // Resizing is limited by the end of the grid
+        let result = preview(&events, &options, 2, Some(700), Some(50), None).unwrap();
+        assert_eq!((result.starts_at, result.duration), (670, 50));
+        assert_eq!(result.positions.len(), 1);
+
+        // Snapping values close to the limit doesn't overflow
+        let result = preview(&events, &options, 2, Some(u16::MAX), None, Some(60)).unwrap();
+        assert_eq!((result.starts_at, result.duration), (660, 60));
+
+        assert_eq!(preview(&events, &options, 99, Some(0), None, None), None);
+    }
 }
@@ -0,0 +1,416 @@
+use date::{days_in_month, Date};
+use std::fmt;
+use types::Event;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Weekday from BYDAY with optional position inside of the month, e.g. "-1FR"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeekdayNum {
+    pub ordinal: Option<i8>,
+    pub weekday: u8,
+}
+
+/// Subset of RFC 5545 RRULE
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub frequency: Frequency,
+    pub interval: u16,
+    pub by_day: Vec<WeekdayNum>,
+    pub count: Option<u16>,
+    pub until: Option<Date>,
+}
+
+const WEEKDAYS: [&'static str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+fn parse_weekday_num(value: &str) -> Option<WeekdayNum> {
+    if value.len() < 2 {
+        return None;
+    }
+    let (ordinal, weekday) = value.split_at(value.len() - 2);
+    let weekday = WEEKDAYS.iter().position(|name| *name == weekday)? as u8;
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => match ordinal.trim_start_matches('+').parse() {
+            Ok(0) | Err(_) => return None,
+            Ok(ordinal) => Some(ordinal),
+        },
+    };
+
+    Some(WeekdayNum {
+        ordinal: ordinal,
+        weekday: weekday,
+    })
+}
+
+impl fmt::Display for WeekdayNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ordinal) = self.ordinal {
+            write!(f, "{}", ordinal)?;
+        }
+        write!(f, "{}", WEEKDAYS[self.weekday as usize])
+    }
+}
+
+/// Parse date in "YYYYMMDD" format, time part is ignored
+pub fn parse_basic_date(value: &str) -> Option<Date> {
+    let (year, month, day) = (value.get(..4)?, value.get(4..6)?, value.get(6..8)?);
+    Date::parse(&format!("{}-{}-{}", year, month, day))
+}
+
+impl Rule {
+    /// Parse rule like "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10"
+    pub fn parse(rule: &str) -> Option<Rule> {
+        let rule = rule.trim_start_matches("RRULE:");
+        let mut frequency = None;
+        let mut parsed = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: vec![],
+            count: None,
+            until: None,
+        };
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let mut pair = part.splitn(2, '=');
+            let (name, value) = (pair.next()?, pair.next()?);
+            match name {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => parsed.interval = value.parse().ok().filter(|&v| v > 0)?,
+                "COUNT" => parsed.count = Some(value.parse().ok().filter(|&v| v > 0)?),
+                "UNTIL" => parsed.until = Some(parse_basic_date(value)?),
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        parsed.by_day.push(parse_weekday_num(day)?);
+                    }
+                }
+                // Other parts are not supported and ignored
+                _ => {}
+            }
+        }
+
+        parsed.frequency = frequency?;
+        Some(parsed)
+    }
+
+    fn has_weekday(&self, day: Date) -> bool {
+        self.by_day
+            .iter()
+            .any(|by_day| by_day.ordinal.is_none() && by_day.weekday == day.weekday())
+    }
+
+    /// Whether the day belongs to rule started at `start`
+    pub fn matches(&self, start: Date, day: Date) -> bool {
+        if day < start {
+            return false;
+        }
+        let interval = self.interval as i32;
+
+        match self.frequency {
+            Frequency::Daily => {
+                (day.days() - start.days()) % interval == 0
+                    && (self.by_day.is_empty() || self.has_weekday(day))
+            }
+            Frequency::Weekly => {
+                // Weeks start on Monday
+                let start_week = start.days() - start.weekday() as i32;
+                let week = day.days() - day.weekday() as i32;
+                let on_weekday = if self.by_day.is_empty() {
+                    day.weekday() == start.weekday()
+                } else {
+                    self.has_weekday(day)
+                };
+                ((week - start_week) / 7) % interval == 0 && on_weekday
+            }
+            Frequency::Monthly => {
+                let months = (day.year - start.year) * 12 + day.month as i32 - start.month as i32;
+                if months % interval != 0 {
+                    return false;
+                }
+                if self.by_day.is_empty() {
+                    return day.day == start.day;
+                }
+
+                let from_start = (day.day as i8 - 1) / 7 + 1;
+                let from_end =
+                    -((days_in_month(day.year, day.month) as i8 - day.day as i8) / 7 + 1);
+                self.by_day.iter().any(|by_day| {
+                    by_day.weekday == day.weekday()
+                        && match by_day.ordinal {
+                            None => true,
+                            Some(ordinal) => ordinal == from_start || ordinal == from_end,
+                        }
+                })
+            }
+        }
+    }
+
+    /// Dates of occurrences inside of `[from, to)`, COUNT is counted
+    /// from `start` and excluded dates still take part in it, without
+    /// COUNT days before `from` are skipped
+    pub fn occurrences(
+        &self,
+        start: Date,
+        excluded: &Vec<Date>,
+        from: Date,
+        to: Date,
+    ) -> Vec<Date> {
+        let mut dates = vec![];
+        let mut count = 0;
+        let last = match self.until {
+            Some(until) if until < to => until.add_days(1),
+            _ => to,
+        };
+
+        // Matching doesn't depend on earlier days, only COUNT needs them
+        let mut day = match self.count {
+            None if from > start => from,
+            _ => start,
+        };
+        while day < last {
+            if self.matches(start, day) {
+                if day >= from && !excluded.contains(&day) {
+                    dates.push(day);
+                }
+                if let Some(limit) = self.count {
+                    count += 1;
+                    if count == limit {
+                        break;
+                    }
+                }
+            }
+            day = day.add_days(1);
+        }
+
+        dates
+    }
+}
+
+/// Most events expansion keeps, IDs past it wouldn't fit into `u16`
+const MAX_EVENTS: usize = u16::MAX as usize + 1;
+
+/// Replace recurring events by their occurrences inside of `[from, to)`
+/// and drop other events outside of it, IDs are assigned again
+/// while `source_id` is kept, events past `MAX_EVENTS` are dropped
+pub fn expand(events: Vec<Event>, from: Date, to: Date) -> Vec<Event> {
+    let mut expanded = vec![];
+
+    for event in events {
+        match (event.recurrence.clone(), event.date) {
+            (Some(rule), Some(start)) => {
+                for date in rule.occurrences(start, &event.excluded_dates, from, to) {
+                    let mut occurrence = event.clone();
+                    occurrence.date = Some(date);
+                    occurrence.recurrence = None;
+                    occurrence.excluded_dates = vec![];
+                    expanded.push(occurrence);
+                }
+            }
+            (_, Some(date)) if date < from || date >= to => {}
+            _ => expanded.push(event),
+        }
+    }
+
+    expanded.truncate(MAX_EVENTS);
+    for (id, event) in expanded.iter_mut().enumerate() {
+        event.id = id as u16;
+    }
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates(rule: &str, start: Date, from: Date, to: Date) -> Vec<String> {
+        Rule::parse(rule)
+            .unwrap()
+            .occurrences(start, &vec![], from, to)
+            .iter()
+            .map(|date| date.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Rule::parse(
+                "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,-1FR;COUNT=10;UNTIL=20261231T000000Z"
+            ),
+            Some(Rule {
+                frequency: Frequency::Weekly,
+                interval: 2,
+                by_day: vec![
+                    WeekdayNum {
+                        ordinal: None,
+                        weekday: 0,
+                    },
+                    WeekdayNum {
+                        ordinal: Some(-1),
+                        weekday: 4,
+                    },
+                ],
+                count: Some(10),
+                until: Some(Date::new(2026, 12, 31)),
+            })
+        );
+        assert_eq!(Rule::parse("INTERVAL=2"), None);
+        assert_eq!(Rule::parse("FREQ=YEARLY"), None);
+        assert_eq!(Rule::parse("FREQ=DAILY;INTERVAL=0"), None);
+        assert_eq!(Rule::parse("FREQ=DAILY;COUNT=0"), None);
+        assert_eq!(Rule::parse("FREQ=DAILY;BYDAY=XX"), None);
+    }
+
+    #[test]
+    fn test_daily() {
+        let start = Date::new(2026, 10, 1);
+        assert_eq!(
+            dates(
+                "FREQ=DAILY;INTERVAL=2",
+                start,
+                Date::new(2026, 10, 4),
+                Date::new(2026, 10, 10)
+            ),
+            vec!["2026-10-05", "2026-10-07", "2026-10-09"]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR;COUNT=3",
+                start,
+                start,
+                Date::new(2026, 11, 1)
+            ),
+            vec!["2026-10-01", "2026-10-02", "2026-10-05"]
+        );
+        // Walk starts at the window, interval is still counted from start
+        assert_eq!(
+            dates(
+                "FREQ=DAILY;INTERVAL=3",
+                Date::new(2000, 1, 1),
+                Date::new(2026, 10, 19),
+                Date::new(2026, 10, 25)
+            ),
+            vec!["2026-10-20", "2026-10-23"]
+        );
+    }
+
+    #[test]
+    fn test_weekly() {
+        // 2026-10-01 is Thursday
+        let start = Date::new(2026, 10, 1);
+        assert_eq!(
+            dates(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH",
+                start,
+                start,
+                Date::new(2026, 10, 31)
+            ),
+            vec![
+                "2026-10-01",
+                "2026-10-12",
+                "2026-10-15",
+                "2026-10-26",
+                "2026-10-29"
+            ]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=WEEKLY;UNTIL=20261015",
+                start,
+                start,
+                Date::new(2026, 12, 1)
+            ),
+            vec!["2026-10-01", "2026-10-08", "2026-10-15"]
+        );
+    }
+
+    #[test]
+    fn test_monthly() {
+        let start = Date::new(2026, 1, 31);
+        assert_eq!(
+            dates("FREQ=MONTHLY;COUNT=3", start, start, Date::new(2027, 1, 1)),
+            vec!["2026-01-31", "2026-03-31", "2026-05-31"]
+        );
+        assert_eq!(
+            dates(
+                "FREQ=MONTHLY;BYDAY=1MO,-1FR",
+                Date::new(2026, 10, 1),
+                Date::new(2026, 10, 1),
+                Date::new(2026, 12, 1)
+            ),
+            vec!["2026-10-05", "2026-10-30", "2026-11-02", "2026-11-27"]
+        );
+    }
+
+    #[test]
+    fn test_expand() {
+        let standup = Event {
+            id: 0,
+            title: Some("Standup".to_string()),
+            date: Some(Date::new(2026, 10, 1)),
+            recurrence: Rule::parse("FREQ=DAILY;COUNT=5"),
+            excluded_dates: vec![Date::new(2026, 10, 3)],
+            ..Default::default()
+        };
+        let other_day = Event {
+            id: 1,
+            date: Some(Date::new(2026, 9, 1)),
+            ..Default::default()
+        };
+        let undated = Event {
+            id: 2,
+            ..Default::default()
+        };
+
+        let expanded = expand(
+            vec![standup, other_day, undated],
+            Date::new(2026, 10, 2),
+            Date::new(2026, 10, 10),
+        );
+        let summary: Vec<(u16, Option<String>)> = expanded
+            .iter()
+            .map(|event| (event.id, event.date.map(|date| date.to_string())))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (0, Some("2026-10-02".to_string())),
+                (1, Some("2026-10-04".to_string())),
+                (2, Some("2026-10-05".to_string())),
+                (3, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_limit() {
+        let daily = Event {
+            date: Some(Date::new(2026, 1, 1)),
+            recurrence: Rule::parse("FREQ=DAILY"),
+            ..Default::default()
+        };
+
+        let expanded = expand(
+            vec![daily],
+            Date::new(2026, 1, 1),
+            Date::new(2026, 1, 1).add_days(MAX_EVENTS as i32 + 10),
+        );
+
+        assert_eq!(expanded.len(), MAX_EVENTS);
+        assert_eq!(expanded[MAX_EVENTS - 1].id, u16::MAX);
+    }
+}
@@ -0,0 +1,222 @@
+use positions;
+use svg;
+use types::{Event, EventPosition, HTMLElement, Options, Orientation};
+
+const UNASSIGNED: &'static str = "Other";
+
+/// Group events by room or person, resources listed in `order` go first
+pub fn group(events: &Vec<Event>, order: &Vec<String>) -> Vec<(String, Vec<Event>)> {
+    let mut groups: Vec<(String, Vec<Event>)> = order
+        .into_iter()
+        .map(|name| (name.to_owned(), vec![]))
+        .collect();
+
+    for event in events {
+        let name = match event.resource_name() {
+            Some(name) => name.to_owned(),
+            None => UNASSIGNED.to_string(),
+        };
+
+        match groups
+            .iter()
+            .position(|(group_name, _)| *group_name == name)
+        {
+            Some(idx) => groups[idx].1.push(event.clone()),
+            None => groups.push((name, vec![event.clone()])),
+        }
+    }
+
+    groups
+}
+
+fn create_column_container<'a>(
+    name: String,
+    idx: usize,
+    column_size: f32,
+    options: &Options,
+) -> HTMLElement<'a> {
+    let offset = idx as f32 * column_size;
+    let g = HTMLElement::new("g").role("list").aria_label(&name);
+    let separator = HTMLElement::new("line").stroke("#e8e8e8");
+    let label = HTMLElement::new("text")
+        .style("font-weight: bold;")
+        .fill("#919191");
+    let (mut g, separator, mut label) = match options.orientation {
+        Orientation::Vertical => (
+            g.translate(offset, 0),
+            separator.y2(svg::SVG_HEIGHT),
+            label.text_anchor("middle").x(column_size / 2.0).y(-10),
+        ),
+        Orientation::Horizontal => (
+            g.translate(0, offset),
+            separator.x2(svg::SVG_HEIGHT),
+            label
+                .text_anchor("end")
+                .dy(".35em")
+                .x(-10)
+                .y(column_size / 2.0),
+        ),
+    };
+
+    // First column is limited by the grid itself
+    if idx > 0 {
+        g.append_child(separator);
+    }
+
+    label.append_child(name);
+    g.append_child(label);
+
+    g
+}
+
+pub fn render(events: &Vec<Event>, options: &Options) -> String {
+    let columns = group(events, &options.resources);
+    let columns_count = columns.len().max(1);
+    let column_size = svg::EVENT_WIDTH as f32 / columns_count as f32;
+
+    let mut svg = svg::create_svg_container(options);
+
+    let (title, desc) = svg::create_summary(events.len(), options);
+    svg.append_child(title);
+    svg.append_child(desc);
+
+    if let Some(defs) = svg::create_defs(events) {
+        svg.append_child(defs);
+    }
+
+    svg.append_child(svg::create_grid_container(options));
+
+    let mut events_container = svg::create_events_container(options);
+    for (idx, (name, column_events)) in columns.into_iter().enumerate() {
+        let mut column = create_column_container(name, idx, column_size, options);
+
+        // Run overlap layout inside of the column only,
+        // screen readers follow start time instead of columns
+        let mut column_positions: Vec<(&Event, EventPosition)> =
+            positions::layout(column_events.clone(), options)
+                .into_iter()
+                .map(|pos| {
+                    let event = column_events
+                        .iter()
+                        .find(|event| event.id == pos.id)
+                        .unwrap();
+                    (event, pos)
+                })
+                .collect();
+        column_positions
+            .sort_by_key(|(event, _)| (positions::takes_space(event, options), event.starts_at));
+
+        for (event, mut pos) in column_positions {
+            pos.width_multiplier /= columns_count as f32;
+
+            let (clip_path, event_container) = svg::create_event(event, &pos, options);
+            svg.append_child(clip_path);
+            column.append_child(event_container);
+        }
+
+        events_container.append_child(column);
+    }
+    svg.append_child(events_container);
+
+    if let Some(marker) = svg::create_now_marker(options) {
+        svg.append_child(marker);
+    }
+
+    svg::markup(&svg, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: u16, resource: Option<&str>, location: Option<&str>) -> Event {
+        Event {
+            id: id,
+            starts_at: 0,
+            duration: 30,
+            resource: resource.map(|name| name.to_string()),
+            location: location.map(|name| name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group() {
+        let events = vec![
+            event(0, Some("Alice"), Some("Room 1")),
+            event(1, None, Some("Room 1")),
+            event(2, None, None),
+            event(3, Some("Bob"), None),
+            event(4, Some("Alice"), None),
+        ];
+        let groups = group(&events, &vec!["Bob".to_string(), "Room 2".to_string()]);
+        let ids: Vec<(&str, Vec<u16>)> = groups
+            .iter()
+            .map(|(name, events)| (name.as_str(), events.iter().map(|e| e.id).collect()))
+            .collect();
+
+        assert_eq!(
+            ids,
+            vec![
+                ("Bob", vec![3]),
+                ("Room 2", vec![]),
+                ("Alice", vec![0, 4]),
+                ("Room 1", vec![1]),
+                ("Other", vec![2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let mut events = vec![
+            event(0, Some("Alice"), None),
+            event(1, Some("Alice"), None),
+            event(2, None, None),
+        ];
+        events[1].title = Some("Overlap".to_string());
+        let svg = render(&events, &Options::default());
+
+        // Overlapping events split their column only
+        assert_eq!(
+            svg.matches("<rect height=\"30\" width=\"145\"/>").count(),
+            2
+        );
+        assert_eq!(
+            svg.matches("<rect height=\"30\" width=\"290\"/>").count(),
+            1
+        );
+        assert!(svg.contains("transform=\"translate(145,0)\""));
+        assert!(svg.contains(
+            "<g aria-label=\"Other\" role=\"list\" transform=\"translate(290,0)\"><line stroke=\"#e8e8e8\" y2=\"720\"/>"
+        ));
+        assert!(svg.contains(
+            "<text fill=\"#919191\" style=\"font-weight: bold;\" text-anchor=\"middle\" x=\"145\" y=\"-10\">Alice</text>"
+        ));
+    }
+
+    #[test]
+    fn test_render_horizontal() {
+        let events = vec![
+            event(0, Some("Alice"), None),
+            event(1, None, Some("Room 1")),
+        ];
+        let options = Options {
+            orientation: Orientation::Horizontal,
+            resources: vec!["Room 1".to_string()],
+            ..Default::default()
+        };
+        let svg = render(&events, &options);
+
+        assert!(svg.contains(
+            "<g aria-label=\"Alice\" role=\"list\" transform=\"translate(0,290)\"><line stroke=\"#e8e8e8\" x2=\"720\"/>"
+        ));
+        assert!(svg.contains(
+            "<text dy=\".35em\" fill=\"#919191\" style=\"font-weight: bold;\" text-anchor=\"end\" x=\"-10\" y=\"145\">Room 1</text>"
+        ));
+        assert_eq!(
+            svg.matches("<rect height=\"290\" width=\"30\"/>").count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use matrix;
+use std::cmp;
+use std::collections::BTreeMap;
+use svg;
+use types::{Event, Options, Stats, Status, TimeRange};
+
+/// Part of the day statistics are calculated for, working hours
+/// are cut so their end still fits into `u16`
+fn window(options: &Options) -> TimeRange {
+    match options.working_hours {
+        Some(ref working_hours) => TimeRange {
+            starts_at: working_hours.starts_at,
+            duration: cmp::min(working_hours.duration, u16::MAX - working_hours.starts_at),
+        },
+        None => TimeRange {
+            starts_at: 0,
+            duration: svg::grid_duration(),
+        },
+    }
+}
+
+/// Events taking time inside of the window cut to its bounds and sorted
+fn busy_events(events: &Vec<Event>, window: &TimeRange) -> Vec<Event> {
+    let window_event = Event {
+        starts_at: window.starts_at,
+        duration: window.duration,
+        ..Default::default()
+    };
+
+    let mut busy: Vec<Event> = events
+        .iter()
+        .filter(|event| match event.status {
+            Status::Cancelled | Status::Declined => false,
+            Status::Confirmed | Status::Tentative => true,
+        })
+        .filter(|event| event.overlap(&window_event))
+        .map(|event| {
+            let starts_at = cmp::max(event.starts_at, window_event.starts_at);
+            let ends_at = cmp::min(event.ends_at(), window_event.ends_at());
+            Event {
+                starts_at: starts_at,
+                duration: ends_at - starts_at,
+                ..event.clone()
+            }
+        })
+        .collect();
+    matrix::sort(&mut busy);
+
+    busy
+}
+
+/// Overlapping sorted events merged into continuous blocks
+pub fn merge(events: &Vec<Event>) -> Vec<Event> {
+    let mut blocks: Vec<Event> = vec![];
+
+    for event in events {
+        if let Some(block) = blocks.last_mut() {
+            if block.overlap(event) {
+                let ends_at = cmp::max(block.ends_at(), event.ends_at());
+                block.duration = ends_at - block.starts_at;
+                continue;
+            }
+        }
+        blocks.push(Event {
+            starts_at: event.starts_at,
+            duration: event.duration,
+            ..Default::default()
+        });
+    }
+
+    blocks
+}
+
+fn free_blocks(blocks: &Vec<Event>, window: &TimeRange) -> Vec<TimeRange> {
+    let mut free = vec![];
+    let mut free_from = window.starts_at;
+
+    let window_end = window.starts_at + window.duration;
+    let ranges = blocks
+        .iter()
+        .map(|block| (block.starts_at, block.ends_at()));
+    for (starts_at, ends_at) in ranges.chain(Some((window_end, window_end))) {
+        if starts_at > free_from {
+            free.push(TimeRange {
+                starts_at: free_from,
+                duration: starts_at - free_from,
+            });
+        }
+        free_from = cmp::max(free_from, ends_at);
+    }
+
+    free
+}
+
+/// Number of moments inside of the window when some event starts or
+/// finishes and the set of ongoing events becomes different
+fn context_switches(events: &Vec<Event>, window: &TimeRange) -> u16 {
+    let window_end = window.starts_at + window.duration;
+    let mut moments: Vec<u16> = events
+        .iter()
+        .flat_map(|event| vec![event.starts_at, event.ends_at()])
+        .filter(|&moment| moment > window.starts_at && moment < window_end)
+        .collect();
+    moments.sort();
+    moments.dedup();
+
+    let ongoing = |moment: u16, before: bool| -> Vec<u16> {
+        events
+            .iter()
+            .filter(|event| {
+                if before {
+                    event.starts_at < moment && event.ends_at() >= moment
+                } else {
+                    event.starts_at <= moment && event.ends_at() > moment
+                }
+            })
+            .map(|event| event.id)
+            .collect()
+    };
+
+    moments
+        .into_iter()
+        .filter(|&moment| ongoing(moment, true) != ongoing(moment, false))
+        .count() as u16
+}
+
+/// Overlapping events are all counted so totals can exceed the window
+fn totals<F>(events: &Vec<Event>, key: F) -> BTreeMap<String, u32>
+where
+    F: Fn(&Event) -> Option<&String>,
+{
+    let mut totals = BTreeMap::new();
+    for event in events {
+        if let Some(name) = key(event) {
+            *totals.entry(name.clone()).or_insert(0) += event.duration as u32;
+        }
+    }
+    totals
+}
+
+pub fn calculate(events: &Vec<Event>, options: &Options) -> Stats {
+    let window = window(options);
+    let busy = busy_events(events, &window);
+    let blocks = merge(&busy);
+    let free = free_blocks(&blocks, &window);
+
+    Stats {
+        busy: blocks.iter().map(|block| block.duration).sum(),
+        context_switches: context_switches(&busy, &window),
+        longest_free: free.iter().map(|range| range.duration).max().unwrap_or(0),
+        categories: totals(&busy, |event| event.category.as_ref()),
+        locations: totals(&busy, |event| event.location.as_ref()),
+        focus_blocks: free
+            .into_iter()
+            .filter(|range| range.duration >= options.min_focus_time)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: u16, starts_at: u16, duration: u16, category: &str) -> Event {
+        Event {
+            id: id,
+            starts_at: starts_at,
+            duration: duration,
+            category: Some(category.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn events() -> Vec<Event> {
+        let mut events = vec![
+            event(0, 30, 60, "meeting"),
+            event(1, 0, 60, "meeting"),
+            event(2, 120, 30, "1:1"),
+            event(3, 300, 30, "meeting"),
+        ];
+        events[1].location = Some("Room 1".to_string());
+        events[2].location = Some("Room 1".to_string());
+        events[3].status = Status::Cancelled;
+        events
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut events = events();
+        matrix::sort(&mut events);
+        let blocks: Vec<(u16, u16)> = merge(&events)
+            .iter()
+            .map(|block| (block.starts_at, block.duration))
+            .collect();
+
+        assert_eq!(blocks, vec![(0, 90), (120, 30), (300, 30)]);
+    }
+
+    #[test]
+    fn test_calculate() {
+        let mut categories = BTreeMap::new();
+        categories.insert("1:1".to_string(), 30);
+        categories.insert("meeting".to_string(), 120);
+        let mut locations = BTreeMap::new();
+        locations.insert("Room 1".to_string(), 90);
+
+        assert_eq!(
+            calculate(&events(), &Options::default()),
+            Stats {
+                busy: 120,
+                context_switches: 5,
+                longest_free: 570,
+                focus_blocks: vec![TimeRange {
+                    starts_at: 150,
+                    duration: 570,
+                }],
+                categories: categories,
+                locations: locations,
+            }
+        );
+    }
+
+    #[test]
+    fn test_calculate_working_hours() {
+        let options = Options {
+            working_hours: Some(TimeRange {
+                starts_at: 60,
+                duration: 180,
+            }),
+            ..Default::default()
+        };
+        let stats = calculate(&events(), &options);
+
+        assert_eq!(stats.busy, 60);
+        assert_eq!(stats.context_switches, 3);
+        assert_eq!(stats.longest_free, 90);
+        assert_eq!(
+            stats.focus_blocks,
+            vec![TimeRange {
+                starts_at: 150,
+                duration: 90,
+            }]
+        );
+        assert_eq!(stats.categories["meeting"], 30);
+    }
+
+    #[test]
+    fn test_calculate_huge_working_hours() {
+        let options = Options {
+            working_hours: Some(TimeRange {
+                starts_at: 300,
+                duration: u16::MAX,
+            }),
+            ..Default::default()
+        };
+        let stats = calculate(&events(), &options);
+
+        assert_eq!(stats.busy, 0);
+        assert_eq!(stats.longest_free, u16::MAX - 300);
+    }
+
+    #[test]
+    fn test_calculate_overlapping_totals() {
+        let events: Vec<Event> = (0..100).map(|id| event(id, 0, 720, "meeting")).collect();
+        let stats = calculate(&events, &Options::default());
+
+        assert_eq!(stats.busy, 720);
+        assert_eq!(stats.categories["meeting"], 72000);
+    }
+}
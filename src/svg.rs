@@ -1,70 +1,195 @@
-use types::{Event, EventPosition, HTMLElement};
-
-const SVG_WIDTH: u16 = 600;
-const SVG_HEIGHT: u16 = 720;
+use options;
+use positions;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use types::{Event, EventPosition, HTMLElement, Layout, Options, Orientation, Status, TimeRange};
+
+/// Namespace declared on root `<svg>` so output is a standalone file
+pub const SVG_NS: &'static str = "http://www.w3.org/2000/svg";
+pub const SVG_WIDTH: u16 = 600;
+pub const SVG_HEIGHT: u16 = 720;
+pub const FIRST_HOUR: u16 = 9;
 const HOURS: [&'static str; 13] = [
     "9 AM", "10 AM", "11 AM", "12 PM", "1 PM", "2 PM", "3 PM", "4 PM", "5 PM", "6 PM", "7 PM",
     "8 PM", "9 PM",
 ];
-const EVENT_WIDTH: u16 = SVG_WIDTH - 20;
+pub const EVENT_WIDTH: u16 = SVG_WIDTH - 20;
+const CASCADE_INDENT: f32 = 20.0;
+/// Indented events of cascade are never narrower than this
+const MIN_CASCADE_WIDTH: f32 = 40.0;
+const TENTATIVE_PATTERN_ID: &'static str = "tentative-hatch";
 static mut CLIP_PATH_ID: u16 = 0;
 lazy_static! {
     static ref HOUR_HEIGHT: f32 = SVG_HEIGHT as f32 / (HOURS.len() - 1) as f32;
 }
 
-fn create_svg_container<'a>() -> HTMLElement<'a> {
-    let view_box = format!("0 0 {} {}", SVG_WIDTH, SVG_HEIGHT);
+/// Minimal duration in minutes which event occupies on the grid
+pub fn min_event_duration(options: &Options) -> u16 {
+    (options.min_event_height / (*HOUR_HEIGHT / 60.0)).ceil() as u16
+}
+
+/// Event box inside of events container
+pub struct EventRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+pub fn event_rect(event: &Event, pos: &EventPosition, options: &Options) -> EventRect {
+    // Position along time axis
+    let time_offset = *HOUR_HEIGHT / 60.0 * event.starts_at as f32;
+    let time_size = (*HOUR_HEIGHT / 60.0 * event.duration as f32).max(options.min_event_height);
+
+    // Position across time axis
+    let lane_size = EVENT_WIDTH as f32 * pos.width_multiplier;
+    let indent_size =
+        (pos.indent as f32 * CASCADE_INDENT).min((lane_size - MIN_CASCADE_WIDTH).max(0.0));
+    let lane_offset = pos.offset as f32 * lane_size + indent_size;
+    let lane_size = lane_size - indent_size;
+
+    match options.orientation {
+        Orientation::Vertical => EventRect {
+            x: lane_offset,
+            y: time_offset,
+            width: lane_size,
+            height: time_size,
+        },
+        Orientation::Horizontal => EventRect {
+            x: time_offset,
+            y: lane_offset,
+            width: time_size,
+            height: lane_size,
+        },
+    }
+}
+
+pub fn create_svg_container<'a>(options: &Options) -> HTMLElement<'a> {
+    let (width, height) = match options.orientation {
+        Orientation::Vertical => (SVG_WIDTH, SVG_HEIGHT),
+        Orientation::Horizontal => (SVG_HEIGHT, SVG_WIDTH),
+    };
     let style = r#"
        overflow: visible;
        font-family: Helvetica, Arial;
        font-size: 0.8em;
-    "#.to_string();
-    HTMLElement::new(
-        "svg",
-        &[
-            ("width", SVG_WIDTH.to_string()),
-            ("height", SVG_HEIGHT.to_string()),
-            ("viewBox", view_box),
-            ("style", style),
-        ],
-    )
+    "#;
+    HTMLElement::new("svg")
+        .xmlns(SVG_NS)
+        .width(width)
+        .height(height)
+        .view_box(width, height)
+        .style(style)
+}
+
+/// Shared definitions like hatching of tentative events
+pub fn create_defs<'a>(events: &Vec<Event>) -> Option<HTMLElement<'a>> {
+    if !events.iter().any(|event| event.status == Status::Tentative) {
+        return None;
+    }
+
+    let mut pattern = HTMLElement::new("pattern")
+        .id(TENTATIVE_PATTERN_ID)
+        .pattern_units("userSpaceOnUse")
+        .width(8)
+        .height(8)
+        .pattern_transform("rotate(45)");
+    pattern.append_child(
+        HTMLElement::new("line")
+            .y2(8)
+            .stroke("#45a51c")
+            .stroke_width(4),
+    );
+
+    let mut defs = HTMLElement::new("defs");
+    defs.append_child(pattern);
+    Some(defs)
 }
 
-fn create_events_container<'a>() -> HTMLElement<'a> {
-    HTMLElement::new("g", &[("transform", "translate(10,0)".to_string())])
+/// Offset of events container inside of the grid
+pub fn events_offset(options: &Options) -> (f32, f32) {
+    match options.orientation {
+        Orientation::Vertical => (10.0, 0.0),
+        Orientation::Horizontal => (0.0, 10.0),
+    }
 }
 
-fn create_grid_container<'a>() -> HTMLElement<'a> {
-    let mut g = HTMLElement::new("g", &[("stroke", "#f8f8f8".to_string())]);
+pub fn create_events_container<'a>(options: &Options) -> HTMLElement<'a> {
+    let (x, y) = events_offset(options);
+    HTMLElement::new("g")
+        .translate(x, y)
+        .role("list")
+        .aria_label("Events")
+}
+
+/// Title and description of the whole schedule for screen readers
+pub fn create_summary<'a>(
+    events_count: usize,
+    options: &Options,
+) -> (HTMLElement<'a>, HTMLElement<'a>) {
+    let mut title = HTMLElement::new("title");
+    match options.date {
+        Some(ref date) => title.append_child(format!("Schedule for {}", date)),
+        None => title.append_child("Schedule"),
+    }
+
+    let mut desc = HTMLElement::new("desc");
+    desc.append_child(format!(
+        "{} {}, {} to {}",
+        events_count,
+        if events_count == 1 { "event" } else { "events" },
+        HOURS[0],
+        HOURS[HOURS.len() - 1],
+    ));
+
+    (title, desc)
+}
+
+pub fn create_grid_container<'a>(options: &Options) -> HTMLElement<'a> {
+    let mut g = HTMLElement::new("g").stroke("#f8f8f8");
+
+    if let Some(ref working_hours) = options.working_hours {
+        g.append_child(create_time_band(working_hours, "#f4faf1", options));
+    }
+    for blocked in &options.blocked {
+        g.append_child(create_time_band(blocked, "#efefef", options));
+    }
+
+    match options.minor_grid {
+        Some(step) if step > 0 && step < 60 => {
+            for minute in (step..grid_duration()).step_by(step as usize) {
+                if minute % 60 != 0 {
+                    g.append_child(create_minor_line(minute, options));
+                }
+            }
+        }
+        _ => {}
+    }
 
     for (i, hour) in HOURS.into_iter().enumerate() {
-        g.append_child(create_hour_group(
-            hour,
-            (i as f32 * *HOUR_HEIGHT).to_string(),
-        ));
+        g.append_child(create_hour_group(hour, i as f32 * *HOUR_HEIGHT, options));
     }
 
-    g.append_child(create_vertical_line(SVG_WIDTH.to_string()));
-    g.append_child(create_vertical_line("0".to_string()));
+    g.append_child(create_boundary_line(SVG_WIDTH as f32, options));
+    g.append_child(create_boundary_line(0.0, options));
 
     g
 }
 
-fn create_hour_group<'a>(hour: &'a str, offset: String) -> HTMLElement<'a> {
-    let translate = format!("translate(0,{})", offset);
-    let mut g = HTMLElement::new("g", &[("transform", translate)]);
-
-    let line = HTMLElement::new("line", &[("x2", SVG_WIDTH.to_string())]);
-    let mut text = HTMLElement::new(
-        "text",
-        &[
-            ("text-anchor", "end".to_string()),
-            ("dy", ".35em".to_string()),
-            ("x", "-10".to_string()),
-            ("fill", "#c9c9c9".to_string()),
-            ("stroke", "none".to_string()),
-        ],
-    );
+fn create_hour_group<'a>(hour: &'a str, offset: f32, options: &Options) -> HTMLElement<'a> {
+    let text = HTMLElement::new("text").fill("#c9c9c9").stroke("none");
+    let (mut g, line, mut text) = match options.orientation {
+        Orientation::Vertical => (
+            HTMLElement::new("g").translate(0, offset),
+            HTMLElement::new("line").x2(SVG_WIDTH),
+            text.text_anchor("end").dy(".35em").x(-10),
+        ),
+        Orientation::Horizontal => (
+            HTMLElement::new("g").translate(offset, 0),
+            HTMLElement::new("line").y2(SVG_WIDTH),
+            text.text_anchor("middle").y(-10),
+        ),
+    };
     text.append_child(hour);
 
     g.append_child(line);
@@ -72,140 +197,235 @@ fn create_hour_group<'a>(hour: &'a str, offset: String) -> HTMLElement<'a> {
     g
 }
 
-fn create_vertical_line<'a>(offset: String) -> HTMLElement<'a> {
-    let translate = format!("translate({},0)", offset);
-    HTMLElement::new(
-        "line",
-        &[("transform", translate), ("y2", SVG_HEIGHT.to_string())],
-    )
+/// Background for range of time across the whole grid
+fn create_time_band<'a>(range: &TimeRange, fill: &str, options: &Options) -> HTMLElement<'a> {
+    let offset = *HOUR_HEIGHT / 60.0 * range.starts_at as f32;
+    let size = *HOUR_HEIGHT / 60.0 * range.duration as f32;
+    let rect = HTMLElement::new("rect").fill(fill).stroke("none");
+    match options.orientation {
+        Orientation::Vertical => rect.y(offset).width(SVG_WIDTH).height(size),
+        Orientation::Horizontal => rect.x(offset).width(size).height(SVG_WIDTH),
+    }
 }
 
-fn create_event_title<'a>(event: &Event) -> Option<HTMLElement<'a>> {
-    if event.title == None && event.location == None {
-        return None;
+fn create_minor_line<'a>(minute: u16, options: &Options) -> HTMLElement<'a> {
+    let offset = *HOUR_HEIGHT / 60.0 * minute as f32;
+    let line = HTMLElement::new("line").stroke_dasharray("4,4");
+    match options.orientation {
+        Orientation::Vertical => line.translate(0, offset).x2(SVG_WIDTH),
+        Orientation::Horizontal => line.translate(offset, 0).y2(SVG_WIDTH),
     }
+}
 
-    let mut tspan = HTMLElement::new(
-        "tspan",
-        &[
-            ("style", "font-weight: bold;".to_string()),
-            ("dy", "-0.1em".to_string()),
-        ],
-    );
-    let mut text = String::new();
-    if let Some(title) = &event.title {
-        text = format!("{}", title);
+/// Line which limits the grid across time axis
+fn create_boundary_line<'a>(offset: f32, options: &Options) -> HTMLElement<'a> {
+    let line = HTMLElement::new("line");
+    match options.orientation {
+        Orientation::Vertical => line.translate(offset, 0).y2(SVG_HEIGHT),
+        Orientation::Horizontal => line.translate(0, offset).x2(SVG_HEIGHT),
+    }
+}
 
-        if let Some(location) = &event.location {
-            text = format!("{}/{}", text, location);
-        }
-    } else if let Some(location) = &event.location {
-        text = format!("{}", location);
+/// Title and location of event joined with slash
+pub fn event_label(event: &Event) -> Option<String> {
+    match (&event.title, &event.location) {
+        (Some(title), Some(location)) => Some(format!("{}/{}", title, location)),
+        (Some(title), None) => Some(title.to_owned()),
+        (None, Some(location)) => Some(location.to_owned()),
+        (None, None) => None,
     }
+}
+
+fn create_event_title<'a>(event: &Event, options: &Options) -> Option<HTMLElement<'a>> {
+    let text = event_label(event)?;
+
+    // Title and time share one line in horizontal orientation
+    let dy = match options.orientation {
+        Orientation::Vertical => "-0.1em",
+        Orientation::Horizontal => "0.35em",
+    };
+    let mut tspan = HTMLElement::new("tspan").style("font-weight: bold;").dy(dy);
     tspan.append_child(text);
 
     Some(tspan)
 }
 
-fn format_interval(interval: u16) -> String {
+pub fn format_interval(interval: u16) -> String {
     let minute = interval % 60;
-    let hour = (interval - minute) / 60 + 9;
+    let hour = (interval - minute) / 60 + FIRST_HOUR;
     let period = if hour < 12 { "AM" } else { "PM" };
 
     let mut result = (if hour > 12 { hour - 12 } else { hour }).to_string();
 
     if minute != 0 {
-        result = format!("{}:{}", result, minute);
+        result = format!("{}:{:02}", result, minute);
     }
 
     format!("{} {}", result, period)
 }
 
-fn create_event_time<'a>(event: &Event, has_title: bool) -> HTMLElement<'a> {
-    let dy = if has_title { "0.9em" } else { "0.35em" };
-    let mut tspan = HTMLElement::new("tspan", &[("dy", dy.to_string()), ("x", "0".to_string())]);
-    tspan.append_child(format!(
+/// Text alternative for event block
+pub fn event_description(event: &Event) -> String {
+    let mut parts = vec![];
+    if let Some(title) = &event.title {
+        parts.push(title.to_owned());
+    }
+    if let Some(location) = &event.location {
+        parts.push(location.to_owned());
+    }
+    parts.push(format_event_time(event));
+    match event.status {
+        Status::Confirmed => {}
+        Status::Tentative => parts.push("tentative".to_string()),
+        Status::Cancelled => parts.push("cancelled".to_string()),
+        Status::Declined => parts.push("declined".to_string()),
+    }
+    parts.join(", ")
+}
+
+/// Time range of event, real times are shown even if event is snapped to grid
+pub fn format_event_time(event: &Event) -> String {
+    let (starts_at, ends_at) = event.label_range();
+    format_time_range(starts_at, ends_at)
+}
+
+pub fn format_time_range(starts_at: u16, ends_at: u16) -> String {
+    format!(
         "{} – {}",
-        format_interval(event.starts_at),
-        format_interval(event.ends_at()),
-    ));
+        format_interval(starts_at),
+        format_interval(ends_at)
+    )
+}
+
+fn create_event_time<'a>(event: &Event, has_title: bool, options: &Options) -> HTMLElement<'a> {
+    let mut tspan = match (&options.orientation, has_title) {
+        (Orientation::Horizontal, true) => HTMLElement::new("tspan").dx("0.5em"),
+        (Orientation::Vertical, true) => HTMLElement::new("tspan").dy("0.9em").x(0),
+        (_, false) => HTMLElement::new("tspan").dy("0.35em").x(0),
+    };
+    tspan.append_child(format_event_time(event));
 
     tspan
 }
 
-fn create_clip_path<'a>(event: &Event, width_multiplier: f32) -> (String, HTMLElement<'a>) {
-    let width = EVENT_WIDTH as f32 * width_multiplier;
-    let height = *HOUR_HEIGHT / 60.0 * event.duration as f32;
+pub fn create_clip_path<'a>(rect: &EventRect) -> (String, HTMLElement<'a>) {
     unsafe {
         CLIP_PATH_ID += 1;
     }
 
     let id = format!("clip-path-{}", unsafe { CLIP_PATH_ID });
-    let mut clip_path = HTMLElement::new("clipPath", &[("id", id.to_owned())]);
-    let rect = HTMLElement::new(
-        "rect",
-        &[("width", width.to_string()), ("height", height.to_string())],
+    create_clip_path_with_id(id, rect)
+}
+
+fn create_clip_path_with_id<'a>(id: String, rect: &EventRect) -> (String, HTMLElement<'a>) {
+    let mut clip_path = HTMLElement::new("clipPath").id(&id);
+    clip_path.append_child(
+        HTMLElement::new("rect")
+            .width(rect.width)
+            .height(rect.height),
     );
-    clip_path.append_child(rect);
 
     (id, clip_path)
 }
 
 fn create_event_container<'a>(
     event: &Event,
-    width_multiplier: f32,
-    offset: u8,
+    pos: &EventPosition,
+    rect: &EventRect,
     clip_path_id: String,
+    options: &Options,
 ) -> HTMLElement<'a> {
-    let width = EVENT_WIDTH as f32 * width_multiplier;
-    let height = *HOUR_HEIGHT / 60.0 * event.duration as f32;
-
-    let translate = format!("translate({},{})", offset as f32 * width, event.starts_at);
-    let mut g = HTMLElement::new(
-        "g",
-        &[
-            ("transform", translate),
-            ("clip-path", format!("url(#{})", clip_path_id)),
-        ],
-    );
+    let (width, height) = (rect.width, rect.height);
+
+    let mut g = HTMLElement::new("g")
+        .clip_path(&format!("url(#{})", clip_path_id))
+        .role("listitem")
+        .aria_label(&event_description(event))
+        .tabindex(0)
+        .translate(rect.x, rect.y);
+
+    // Only allowed fields are passed through to not leak anything by accident
+    for name in &options.data_attributes {
+        match event.extra.get(name) {
+            Some(Value::String(value)) => g.data_attr(name, value.to_owned()),
+            Some(value) => g.data_attr(name, value.to_string()),
+            None => {}
+        }
+    }
 
-    let rect = HTMLElement::new(
-        "rect",
-        &[
-            ("width", width.to_string()),
-            ("height", height.to_string()),
-            ("fill", "#45a51c".to_string()),
-            ("opacity", "0.3".to_string()),
-        ],
-    );
+    let now = options::now(options);
+    let in_progress = match now {
+        Some(now) => event.starts_at as i32 <= now && now < event.ends_at() as i32,
+        None => false,
+    };
+    let mut opacity = 1.0;
+    // Dim events which are already over
+    if let Some(now) = now {
+        if event.ends_at() as i32 <= now {
+            opacity *= 0.5;
+        }
+    }
+    if event.status == Status::Cancelled || event.status == Status::Declined {
+        opacity *= 0.4;
+    }
+    if opacity < 1.0 {
+        g = g.opacity(opacity);
+    }
+
+    // Hide events below when they are stacked
+    if options.layout == Layout::Cascade && pos.indent > 0 {
+        let background = HTMLElement::new("rect")
+            .width(width)
+            .height(height)
+            .fill("#fff");
+        g.append_child(background);
+    }
+
+    let fill = match event.status {
+        Status::Tentative => format!("url(#{})", TENTATIVE_PATTERN_ID),
+        _ => "#45a51c".to_string(),
+    };
+    let rect = HTMLElement::new("rect")
+        .width(width)
+        .height(height)
+        .fill(&fill)
+        .opacity(0.3);
     g.append_child(rect);
 
-    let line = HTMLElement::new(
-        "line",
-        &[
-            ("stroke-width", 4.to_string()),
-            ("stroke", "#45a51c".to_string()),
-            ("y2", height.to_string()),
-        ],
-    );
+    let line = HTMLElement::new("line")
+        .stroke_width(4)
+        .stroke("#45a51c")
+        .y2(height);
     g.append_child(line);
 
-    let mut text = HTMLElement::new(
-        "text",
-        &[
-            ("transform", format!("translate(10,{})", height / 2.0)),
-            ("fill", "#45a51c".to_string()),
-        ],
-    );
+    // Outline is inset to stay visible inside of clip path
+    if in_progress {
+        let outline = HTMLElement::new("rect")
+            .x(1)
+            .y(1)
+            .width((width - 2.0).max(0.0))
+            .height((height - 2.0).max(0.0))
+            .fill("none")
+            .stroke("#45a51c")
+            .stroke_width(2);
+        g.append_child(outline);
+    }
 
-    let title_container = create_event_title(&event);
+    let mut text = HTMLElement::new("text")
+        .translate(10, height / 2.0)
+        .fill("#45a51c");
+    if event.status == Status::Cancelled {
+        text = text.text_decoration("line-through");
+    }
+
+    let title_container = create_event_title(&event, options);
     match title_container {
         Some(title) => {
             text.append_child(title);
-            text.append_child(create_event_time(&event, true));
+            text.append_child(create_event_time(&event, true, options));
         }
         None => {
-            text.append_child(create_event_time(&event, false));
+            text.append_child(create_event_time(&event, false, options));
         }
     }
     g.append_child(text);
@@ -213,21 +433,590 @@ fn create_event_container<'a>(
     g
 }
 
-pub fn render(events: &Vec<Event>, positions: &Vec<EventPosition>) -> String {
-    let mut svg = create_svg_container();
+/// Line across the grid at current time
+pub fn create_now_marker<'a>(options: &Options) -> Option<HTMLElement<'a>> {
+    let now = options::now(options)?;
+    let offset = *HOUR_HEIGHT / 60.0 * now as f32;
+    if offset < 0.0 || offset > SVG_HEIGHT as f32 {
+        return None;
+    }
+
+    let g = HTMLElement::new("g").stroke("#e0393e").fill("#e0393e");
+    let line = HTMLElement::new("line").stroke_width(2);
+    let (mut g, line) = match options.orientation {
+        Orientation::Vertical => (g.translate(0, offset), line.x2(SVG_WIDTH)),
+        Orientation::Horizontal => (g.translate(offset, 0), line.y2(SVG_WIDTH)),
+    };
+    g.append_child(line);
+    g.append_child(HTMLElement::new("circle").r(4));
+
+    Some(g)
+}
+
+/// Create clip path and container for event
+pub fn create_event<'a>(
+    event: &Event,
+    pos: &EventPosition,
+    options: &Options,
+) -> (HTMLElement<'a>, HTMLElement<'a>) {
+    let rect = event_rect(&event, pos, options);
+    let clip_path = create_clip_path(&rect);
+    let event_container = create_event_container(&event, pos, &rect, clip_path.0, options);
+    (clip_path.1, event_container)
+}
+
+/// Part of element id made of any key, characters other than ASCII
+/// letters, digits and '-' are escaped as "_<hex code>_" so different
+/// keys never give the same id
+pub fn escape_id(key: &str) -> String {
+    let mut id = String::new();
+    for c in key.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' => id.push(c),
+            _ => id.push_str(&format!("_{:x}_", c as u32)),
+        }
+    }
+    id
+}
+
+/// Keys of events by id, events sharing the key like occurrences of
+/// recurring event or events with the same UID get "#<n>" suffix
+pub fn unique_keys(events: &Vec<Event>) -> Vec<String> {
+    let mut used = BTreeSet::new();
+    events
+        .iter()
+        .map(|event| {
+            let key = event.key();
+            let mut unique = key.clone();
+            let mut occurrence = 1;
+            while used.contains(&unique) {
+                unique = format!("{}#{}", key, occurrence);
+                occurrence += 1;
+            }
+            used.insert(unique.clone());
+            unique
+        })
+        .collect()
+}
 
-    svg.append_child(create_grid_container());
+/// Same as `create_event` with clip path id and keys derived from
+/// `key` of the event so they stay the same between renders
+pub fn create_keyed_event<'a>(
+    event: &Event,
+    pos: &EventPosition,
+    key: &str,
+    options: &Options,
+) -> (HTMLElement<'a>, HTMLElement<'a>) {
+    let rect = event_rect(&event, pos, options);
+    let id = format!("clip-path-{}", escape_id(key));
+    let (clip_path_id, mut clip_path) = create_clip_path_with_id(id, &rect);
+    let mut event_container = create_event_container(&event, pos, &rect, clip_path_id, options);
+
+    clip_path.set_key(format!("clip-{}", key));
+    event_container.set_key(format!("event-{}", key));
+    (clip_path, event_container)
+}
 
-    let mut events_container = create_events_container();
-    for pos in positions {
+/// Screen readers and z-order follow start time instead of columns,
+/// cancelled events without own space stay below others
+pub fn ordered_positions<'p>(
+    events: &Vec<Event>,
+    positions: &'p Vec<EventPosition>,
+    options: &Options,
+) -> Vec<&'p EventPosition> {
+    let mut ordered: Vec<&EventPosition> = positions.iter().collect();
+    ordered.sort_by_key(|pos| {
         let event = &events[pos.id as usize];
-        let clip_path = create_clip_path(&event, pos.width_multiplier);
-        let event_container =
-            create_event_container(&event, pos.width_multiplier, pos.offset, clip_path.0);
-        svg.append_child(clip_path.1);
+        (positions::takes_space(event, options), event.starts_at)
+    });
+    ordered
+}
+
+/// ID of the topmost event under the point of the grid
+pub fn event_at(
+    events: &Vec<Event>,
+    positions: &Vec<EventPosition>,
+    options: &Options,
+    x: f32,
+    y: f32,
+) -> Option<u16> {
+    let (offset_x, offset_y) = events_offset(options);
+    let (x, y) = (x - offset_x, y - offset_y);
+
+    ordered_positions(events, positions, options)
+        .into_iter()
+        .rev()
+        .find(|pos| {
+            let rect = event_rect(&events[pos.id as usize], pos, options);
+            rect.x <= x && x < rect.x + rect.width && rect.y <= y && y < rect.y + rect.height
+        })
+        .map(|pos| pos.id)
+}
+
+/// Markup of the view, indented with `pretty` option
+pub fn markup(el: &HTMLElement, options: &Options) -> String {
+    if options.pretty {
+        el.to_pretty_string()
+    } else {
+        el.to_string()
+    }
+}
+
+/// Number of minutes visible on the grid
+pub fn grid_duration() -> u16 {
+    (HOURS.len() as u16 - 1) * 60
+}
+
+/// Minute under the point along time axis of the grid, optionally snapped
+pub fn time_at(offset: f32, snap: Option<u16>) -> u16 {
+    let last_minute = grid_duration() as f32;
+    let minute = (offset / (*HOUR_HEIGHT / 60.0)).max(0.0).min(last_minute);
+
+    match snap {
+        Some(snap) if snap > 0 => {
+            let snapped = (minute / snap as f32).round() * snap as f32;
+            // Rounding up must not leave the grid
+            if snapped > last_minute {
+                snapped as u16 - snap
+            } else {
+                snapped as u16
+            }
+        }
+        _ => minute as u16,
+    }
+}
+
+/// Tree of the day view, with `keyed` all children of the root and
+/// events get keys for patching
+pub fn build<'a>(
+    events: &Vec<Event>,
+    positions: &Vec<EventPosition>,
+    options: &Options,
+    keyed: bool,
+) -> HTMLElement<'a> {
+    let set_key = |el: &mut HTMLElement, key: &str| {
+        if keyed {
+            el.set_key(key.to_string());
+        }
+    };
+    let mut svg = create_svg_container(options);
+    set_key(&mut svg, "schedule");
+
+    let (mut title, mut desc) = create_summary(events.len(), options);
+    set_key(&mut title, "title");
+    set_key(&mut desc, "desc");
+    svg.append_child(title);
+    svg.append_child(desc);
+
+    if let Some(mut defs) = create_defs(events) {
+        set_key(&mut defs, "defs");
+        svg.append_child(defs);
+    }
+
+    let mut grid = create_grid_container(options);
+    set_key(&mut grid, "grid");
+    svg.append_child(grid);
+
+    let mut events_container = create_events_container(options);
+    set_key(&mut events_container, "events");
+    let keys = unique_keys(events);
+    for pos in ordered_positions(events, positions, options) {
+        let event = &events[pos.id as usize];
+        let (clip_path, event_container) = if keyed {
+            create_keyed_event(&event, pos, &keys[pos.id as usize], options)
+        } else {
+            create_event(&event, pos, options)
+        };
+        svg.append_child(clip_path);
         events_container.append_child(event_container);
     }
     svg.append_child(events_container);
 
-    svg.to_string()
+    if let Some(mut marker) = create_now_marker(options) {
+        set_key(&mut marker, "now");
+        svg.append_child(marker);
+    }
+
+    svg
+}
+
+pub fn render(events: &Vec<Event>, positions: &Vec<EventPosition>, options: &Options) -> String {
+    markup(&build(events, positions, options, false), options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use events;
+    use types::{Now, Snap, SnapMode};
+
+    fn event(id: u16, starts_at: u16, duration: u16) -> Event {
+        Event {
+            id: id,
+            starts_at: starts_at,
+            duration: duration,
+            ..Default::default()
+        }
+    }
+
+    fn position(id: u16, width_multiplier: f32, offset: u8, indent: u8) -> EventPosition {
+        EventPosition {
+            id: id,
+            width_multiplier: width_multiplier,
+            offset: offset,
+            indent: indent,
+        }
+    }
+
+    #[test]
+    fn test_event_rect_deep_indent() {
+        let rect = event_rect(
+            &event(0, 0, 60),
+            &position(0, 1.0, 0, 40),
+            &Options::default(),
+        );
+        assert_eq!(rect.x, 540.0);
+        assert_eq!(rect.width, MIN_CASCADE_WIDTH);
+
+        let rect = event_rect(
+            &event(0, 0, 60),
+            &position(0, 0.05, 0, 2),
+            &Options::default(),
+        );
+        assert_eq!(rect.width, 29.0);
+    }
+
+    #[test]
+    fn test_create_event_min_height() {
+        let events = vec![event(0, 300, 5), event(1, 310, 5)];
+        let options = Options {
+            min_event_height: 20.0,
+            ..Default::default()
+        };
+        let positions = positions::layout(events.clone(), &options);
+
+        // Blocks of short events would overlap so they share the row
+        let (clip_path, container) = create_event(&events[0], &positions[0], &options);
+        assert!(clip_path
+            .to_string()
+            .ends_with("<rect height=\"20\" width=\"290\"/></clipPath>"));
+        assert!(container
+            .to_string()
+            .contains("<rect fill=\"#45a51c\" height=\"20\" opacity=\"0.3\" width=\"290\"/>"));
+        let (_, container) = create_event(&events[1], &positions[1], &options);
+        assert!(container
+            .to_string()
+            .contains("transform=\"translate(290,310)\""));
+    }
+
+    #[test]
+    fn test_create_event_cascade() {
+        let events = vec![event(0, 60, 90), event(1, 90, 30)];
+        let options = Options {
+            layout: Layout::Cascade,
+            ..Default::default()
+        };
+        let positions = vec![position(0, 1.0, 0, 0), position(1, 1.0, 0, 1)];
+        let html = build(&events, &positions, &options, false).to_string();
+
+        // Stacked event is indented and hides the one below it
+        let bottom = html.find("aria-label=\"10 AM – 11:30 AM\"").unwrap();
+        let top = html.find("aria-label=\"10:30 AM – 11 AM\"").unwrap();
+        assert!(bottom < top);
+        let (_, container) = create_event(&events[1], &positions[1], &options);
+        let container = container.to_string();
+        assert!(container.contains("transform=\"translate(20,90)\""));
+        assert!(container.contains("<rect fill=\"#fff\" height=\"30\" width=\"560\"/>"));
+        let (_, container) = create_event(&events[0], &positions[0], &options);
+        assert!(!container.to_string().contains("fill=\"#fff\""));
+    }
+
+    #[test]
+    fn test_create_event_snapped() {
+        let options = Options {
+            snap: Some(Snap {
+                interval: 15,
+                mode: SnapMode::Floor,
+            }),
+            ..Default::default()
+        };
+        let events = events::deserialize(r#"[{"starts_at":7,"duration":46}]"#, &options);
+        let (_, container) = create_event(&events[0], &position(0, 1.0, 0, 0), &options);
+        let container = container.to_string();
+
+        // Block sits on the grid, label keeps the real times
+        assert!(container.contains("transform=\"translate(0,0)\""));
+        assert!(container
+            .contains("<rect fill=\"#45a51c\" height=\"45\" opacity=\"0.3\" width=\"580\"/>"));
+        assert!(container.contains("aria-label=\"9:07 AM – 9:53 AM\""));
+    }
+
+    #[test]
+    fn test_create_event_status() {
+        let mut events = vec![event(0, 60, 60), event(1, 70, 30), event(2, 120, 120)];
+        events[0].status = Status::Tentative;
+        events[1].status = Status::Cancelled;
+        events[2].status = Status::Declined;
+        let options = Options {
+            layout_cancelled: false,
+            ..Default::default()
+        };
+        let positions = positions::layout(events.clone(), &options);
+        let container = |id: usize| {
+            create_event(&events[id], &positions[id], &options)
+                .1
+                .to_string()
+        };
+
+        // Cancelled event does not take a column from the tentative one
+        let tentative = container(0);
+        assert!(tentative.contains(
+            "<rect fill=\"url(#tentative-hatch)\" height=\"60\" opacity=\"0.3\" width=\"580\"/>"
+        ));
+        assert!(!tentative.contains("line-through"));
+        let cancelled = container(1);
+        assert!(cancelled.contains("opacity=\"0.4\" role=\"listitem\""));
+        assert!(cancelled.contains("text-decoration=\"line-through\""));
+        let declined = container(2);
+        assert!(declined.contains("opacity=\"0.4\" role=\"listitem\""));
+        assert!(!declined.contains("line-through"));
+
+        assert_eq!(
+            create_defs(&events).unwrap().to_string(),
+            "<defs><pattern height=\"8\" id=\"tentative-hatch\" \
+             patternTransform=\"rotate(45)\" patternUnits=\"userSpaceOnUse\" width=\"8\">\
+             <line stroke=\"#45a51c\" stroke-width=\"4\" y2=\"8\"/></pattern></defs>"
+        );
+        assert!(create_defs(&events[1..].to_vec()).is_none());
+    }
+
+    #[test]
+    fn test_event_at() {
+        let events = vec![event(0, 60, 60), event(1, 90, 60), event(2, 300, 5)];
+        let positions = vec![
+            position(0, 0.5, 0, 0),
+            position(1, 0.5, 1, 0),
+            position(2, 1.0, 0, 0),
+        ];
+        let options = Options::default();
+
+        assert_eq!(
+            event_at(&events, &positions, &options, 20.0, 100.0),
+            Some(0)
+        );
+        assert_eq!(
+            event_at(&events, &positions, &options, 400.0, 100.0),
+            Some(1)
+        );
+        assert_eq!(event_at(&events, &positions, &options, 400.0, 80.0), None);
+        assert_eq!(event_at(&events, &positions, &options, 5.0, 100.0), None);
+        assert_eq!(event_at(&events, &positions, &options, 20.0, 310.0), None);
+
+        let options = Options {
+            min_event_height: 20.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            event_at(&events, &positions, &options, 20.0, 310.0),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_event_at_cascade() {
+        let events = vec![event(0, 60, 90), event(1, 90, 30)];
+        let positions = vec![position(0, 1.0, 0, 0), position(1, 1.0, 0, 1)];
+        let options = Options {
+            layout: Layout::Cascade,
+            ..Default::default()
+        };
+
+        // Later event is drawn on top
+        assert_eq!(
+            event_at(&events, &positions, &options, 40.0, 100.0),
+            Some(1)
+        );
+        assert_eq!(
+            event_at(&events, &positions, &options, 20.0, 100.0),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_event_at_horizontal() {
+        let events = vec![event(0, 60, 60)];
+        let positions = vec![position(0, 0.5, 1, 0)];
+        let options = Options {
+            orientation: Orientation::Horizontal,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            event_at(&events, &positions, &options, 100.0, 310.0),
+            Some(0)
+        );
+        assert_eq!(event_at(&events, &positions, &options, 100.0, 100.0), None);
+    }
+
+    #[test]
+    fn test_create_grid_container() {
+        let count = |options: &Options| {
+            create_grid_container(options)
+                .to_string()
+                .matches("stroke-dasharray")
+                .count()
+        };
+
+        assert_eq!(count(&Options::default()), 0);
+        assert_eq!(
+            count(&Options {
+                minor_grid: Some(30),
+                ..Default::default()
+            }),
+            12
+        );
+        assert_eq!(
+            count(&Options {
+                minor_grid: Some(15),
+                ..Default::default()
+            }),
+            36
+        );
+
+        let grid = create_grid_container(&Options {
+            minor_grid: Some(30),
+            working_hours: Some(TimeRange {
+                starts_at: 0,
+                duration: 480,
+            }),
+            blocked: vec![TimeRange {
+                starts_at: 180,
+                duration: 60,
+            }],
+            ..Default::default()
+        })
+        .to_string();
+        let working = grid
+            .find("<rect fill=\"#f4faf1\" height=\"480\" stroke=\"none\" width=\"600\" y=\"0\"/>")
+            .unwrap();
+        let blocked = grid
+            .find("<rect fill=\"#efefef\" height=\"60\" stroke=\"none\" width=\"600\" y=\"180\"/>")
+            .unwrap();
+        let minor = grid
+            .find("<line stroke-dasharray=\"4,4\" transform=\"translate(0,30)\" x2=\"600\"/>")
+            .unwrap();
+        // Bands are painted first so lines stay visible on top of them
+        assert!(working < blocked && blocked < minor);
+        // Full hours already have a solid line
+        assert!(!grid.contains("stroke-dasharray=\"4,4\" transform=\"translate(0,60)\""));
+    }
+
+    #[test]
+    fn test_horizontal() {
+        let options = Options {
+            orientation: Orientation::Horizontal,
+            ..Default::default()
+        };
+        let grid = create_grid_container(&options).to_string();
+        assert!(grid.contains(
+            "<g transform=\"translate(60,0)\"><line y2=\"600\"/>\
+             <text fill=\"#c9c9c9\" stroke=\"none\" text-anchor=\"middle\" y=\"-10\">10 AM</text></g>"
+        ));
+        assert!(grid.contains("<line transform=\"translate(0,600)\" x2=\"720\"/>"));
+
+        // Lane height is split and labels go along the block
+        let mut standup = event(0, 60, 30);
+        standup.title = Some("Standup".to_string());
+        let (_, container) = create_event(&standup, &position(0, 0.5, 1, 0), &options);
+        let container = container.to_string();
+        assert!(container.contains("transform=\"translate(60,290)\""));
+        assert!(container
+            .contains("<rect fill=\"#45a51c\" height=\"290\" opacity=\"0.3\" width=\"30\"/>"));
+        assert!(container.contains(
+            "<tspan dy=\"0.35em\" style=\"font-weight: bold;\">Standup</tspan>\
+             <tspan dx=\"0.5em\">10 AM – 10:30 AM</tspan>"
+        ));
+    }
+
+    #[test]
+    fn test_now() {
+        let options = Options {
+            now: Some(Now::Timestamp("10:30".to_string())),
+            ..Default::default()
+        };
+        assert_eq!(
+            create_now_marker(&options).unwrap().to_string(),
+            "<g fill=\"#e0393e\" stroke=\"#e0393e\" transform=\"translate(0,90)\">\
+             <line stroke-width=\"2\" x2=\"600\"/><circle r=\"4\"/></g>"
+        );
+        assert!(create_now_marker(&Options {
+            now: Some(Now::Minutes(800)),
+            ..Default::default()
+        })
+        .is_none());
+
+        let pos = position(0, 1.0, 0, 0);
+        let container = |starts_at: u16| {
+            create_event(&event(0, starts_at, 30), &pos, &options)
+                .1
+                .to_string()
+        };
+        let outline = "<rect fill=\"none\" height=\"28\" stroke=\"#45a51c\" \
+                       stroke-width=\"2\" width=\"578\" x=\"1\" y=\"1\"/>";
+
+        // Past event is dimmed, ongoing one is outlined
+        let past = container(30);
+        assert!(past.contains("opacity=\"0.5\" role=\"listitem\""));
+        assert!(!past.contains(outline));
+        let ongoing = container(75);
+        assert!(!ongoing.contains("opacity=\"0.5\""));
+        assert!(ongoing.contains(outline));
+        let future = container(120);
+        assert!(!future.contains("opacity=\"0.5\"") && !future.contains(outline));
+    }
+
+    #[test]
+    fn test_time_at() {
+        assert_eq!(time_at(97.0, None), 97);
+        assert_eq!(time_at(97.0, Some(15)), 90);
+        assert_eq!(time_at(98.0, Some(15)), 105);
+        assert_eq!(time_at(-10.0, Some(15)), 0);
+        assert_eq!(time_at(719.0, Some(30)), 720);
+        assert_eq!(time_at(800.0, Some(25)), 700);
+    }
+
+    #[test]
+    fn test_unique_keys() {
+        let mut events = vec![event(0, 0, 30), event(1, 60, 30), event(2, 120, 30)];
+        events[0].uid = Some("a".to_string());
+        events[1].uid = Some("a#1".to_string());
+        events[2].uid = Some("a".to_string());
+
+        assert_eq!(unique_keys(&events), vec!["uid:a", "uid:a#1", "uid:a#2"]);
+    }
+
+    #[test]
+    fn test_escape_id() {
+        assert_eq!(escape_id("uid:Room A"), "uid_3a_Room_20_A");
+        assert_ne!(escape_id("uid:Room A"), escape_id("uid:room-a"));
+        assert_ne!(escape_id("a_20_"), escape_id("a "));
+    }
+
+    #[test]
+    fn test_build_keyed_duplicate_uids() {
+        let mut events = vec![event(0, 0, 30), event(1, 60, 30)];
+        events[0].uid = Some("Room A".to_string());
+        events[1].uid = Some("Room A".to_string());
+        let positions = vec![position(0, 1.0, 0, 0), position(1, 1.0, 0, 0)];
+        let html = build(&events, &positions, &Options::default(), true).to_string();
+
+        assert_eq!(html.matches("data-key=\"event-uid:Room A\"").count(), 1);
+        assert_eq!(html.matches("data-key=\"event-uid:Room A#1\"").count(), 1);
+        assert_eq!(html.matches("id=\"clip-path-uid_3a_Room_20_A\"").count(), 1);
+        assert_eq!(
+            html.matches("id=\"clip-path-uid_3a_Room_20_A_23_1\"")
+                .count(),
+            1
+        );
+    }
 }
@@ -1,6 +1,11 @@
+use date::Date;
+use recurrence::Rule;
+use serde_json::Value;
+use std::cmp;
 use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::Display;
+use std::io;
 
 pub type EventsMatrix = Vec<Vec<u16>>;
 
@@ -9,25 +14,341 @@ pub struct EventPosition {
     pub id: u16,
     pub width_multiplier: f32,
     pub offset: u8,
+    pub indent: u8,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Layout of events affected by moving or resizing one of them
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Preview {
+    pub id: u16,
+    pub starts_at: u16,
+    pub duration: u16,
+    pub positions: Vec<EventPosition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Confirmed,
+    Tentative,
+    Cancelled,
+    Declined,
+}
+impl Default for Status {
+    fn default() -> Status {
+        Status::Confirmed
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
 pub struct ExternalEvent {
+    /// Identifier from the source calendar used to match changed events
+    pub uid: Option<String>,
     pub starts_at: u16,
     pub duration: u16,
     pub title: Option<String>,
     pub location: Option<String>,
+    pub resource: Option<String>,
+    /// Kind of the event like "meeting" used in statistics
+    pub category: Option<String>,
+    /// Date in "YYYY-MM-DD" format
+    pub date: Option<String>,
+    #[serde(default)]
+    pub status: Status,
+    /// Hide title and location of the event
+    #[serde(default)]
+    pub private: bool,
+    /// Recurrence rule like "FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10"
+    pub rrule: Option<String>,
+    /// Dates excluded from recurrence
+    #[serde(default)]
+    pub exdate: Vec<String>,
+    /// Unknown fields passed through to data attributes
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Overlapping events split into equal columns
+    Columns,
+    /// Overlapping events drawn indented on top of each other
+    Cascade,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// Time runs top to bottom
+    Vertical,
+    /// Time runs left to right
+    Horizontal,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgendaFormat {
+    Svg,
+    Html,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Now {
+    /// Minutes since start of the grid, same as `starts_at`
+    Minutes(u16),
+    /// Local time in "HH:MM" or "YYYY-MM-DDTHH:MM" format, UTC offsets are rejected
+    Timestamp(String),
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapMode {
+    Floor,
+    Ceil,
+    Nearest,
+}
+impl Default for SnapMode {
+    fn default() -> SnapMode {
+        SnapMode::Nearest
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Snap {
+    /// Grid step in minutes, e.g. 5, 10, 15 or 30
+    pub interval: u16,
+    #[serde(default)]
+    pub mode: SnapMode,
+}
+
+/// Range of time on the grid in the same units as events
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub starts_at: u16,
+    pub duration: u16,
+}
+
+/// Names of CSV columns, `end` takes precedence over `duration`
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CsvColumns {
+    pub title: String,
+    pub location: String,
+    /// Minutes since start of the grid or "HH:MM" time
+    pub start: String,
+    /// Same format as `start`
+    pub end: Option<String>,
+    /// Duration in minutes
+    pub duration: Option<String>,
+    /// Date in "YYYY-MM-DD" format
+    pub date: Option<String>,
+}
+impl Default for CsvColumns {
+    fn default() -> CsvColumns {
+        CsvColumns {
+            title: "title".to_string(),
+            location: "location".to_string(),
+            start: "start".to_string(),
+            end: Some("end".to_string()),
+            duration: Some("duration".to_string()),
+            date: Some("date".to_string()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Options {
+    /// Minimal height of event block in pixels
+    pub min_event_height: f32,
+    pub layout: Layout,
+    pub orientation: Orientation,
+    /// Order of columns in resource view
+    pub resources: Vec<String>,
+    /// Markup produced by agenda view
+    pub agenda_format: AgendaFormat,
+    /// Free time in agenda view is shown when it's longer than this many minutes
+    pub min_free_gap: u16,
+    /// Rendered date in "YYYY-MM-DD" format
+    pub date: Option<String>,
+    /// Current time, past events are dimmed
+    pub now: Option<Now>,
+    /// Rounding of event times applied on deserialize
+    pub snap: Option<Snap>,
+    /// Step of minor grid lines in minutes, e.g. 15 or 30
+    pub minor_grid: Option<u16>,
+    /// Highlighted part of the day
+    pub working_hours: Option<TimeRange>,
+    /// Shaded ranges like lunch
+    pub blocked: Vec<TimeRange>,
+    /// Whether cancelled events take space from other events
+    pub layout_cancelled: bool,
+    /// Show only free/busy time of all events
+    pub privacy: bool,
+    /// Title shown instead of details of private events
+    pub busy_label: String,
+    /// Extra fields of events rendered as data attributes
+    pub data_attributes: Vec<String>,
+    /// Column mapping of CSV import and export
+    pub csv_columns: CsvColumns,
+    /// Minimal free time in minutes counted as focus block in statistics
+    pub min_focus_time: u16,
+    /// Size of heatmap cells in minutes
+    pub heatmap_bucket: u16,
+    /// Heatmap colors from the least to the most busy cells
+    pub heatmap_colors: Vec<String>,
+    /// Indent rendered markup for debugging
+    pub pretty: bool,
+}
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            min_event_height: 0.0,
+            layout: Layout::Columns,
+            orientation: Orientation::Vertical,
+            resources: vec![],
+            agenda_format: AgendaFormat::Svg,
+            min_free_gap: 30,
+            date: None,
+            now: None,
+            snap: None,
+            minor_grid: None,
+            working_hours: None,
+            blocked: vec![],
+            layout_cancelled: true,
+            privacy: false,
+            busy_label: "Busy".to_string(),
+            data_attributes: vec![],
+            csv_columns: CsvColumns::default(),
+            min_focus_time: 90,
+            heatmap_bucket: 30,
+            heatmap_colors: vec![
+                "#dcefd2".to_string(),
+                "#a9d98f".to_string(),
+                "#75c04e".to_string(),
+                "#45a51c".to_string(),
+            ],
+            pretty: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Removed,
+    Moved,
+    Resized,
+    Retitled,
+}
+
+/// Changes of one event between old and new schedule
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EventDiff {
+    pub key: String,
+    pub old_id: Option<u16>,
+    pub new_id: Option<u16>,
+    pub changes: Vec<Change>,
+}
+
+/// Keyed element as it was rendered last time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyedNode {
+    pub parent: Option<String>,
+    pub attrs: BTreeMap<String, String>,
+    /// Markup of children unless all of them are keyed
+    pub html: Option<String>,
+    /// Keys of children otherwise
+    pub children: Vec<String>,
+}
+
+/// Operation on live DOM, elements are found by `data-key`
+/// and new elements are inserted before `before` or appended
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOp {
+    Create {
+        key: String,
+        parent: Option<String>,
+        before: Option<String>,
+        html: String,
+    },
+    Update {
+        key: String,
+        attrs: BTreeMap<String, String>,
+        removed_attrs: Vec<String>,
+        html: Option<String>,
+    },
+    Move {
+        key: String,
+        parent: Option<String>,
+        before: Option<String>,
+    },
+    Remove {
+        key: String,
+    },
+}
+
+/// Operations with state to pass into the next call
+#[derive(Debug, Serialize)]
+pub struct Patch {
+    pub state: String,
+    pub ops: Vec<PatchOp>,
+}
+
+/// Summary of the day within working hours or the whole grid
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Stats {
+    /// Minutes taken by events, overlapping events are counted once
+    pub busy: u16,
+    /// How many times the set of ongoing events changes
+    pub context_switches: u16,
+    /// Longest free time in minutes
+    pub longest_free: u16,
+    /// Free blocks of at least `min_focus_time` minutes
+    pub focus_blocks: Vec<TimeRange>,
+    /// Minutes of events by category, overlaps are counted in each category
+    pub categories: BTreeMap<String, u32>,
+    /// Minutes of events by location
+    pub locations: BTreeMap<String, u32>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Event {
     pub id: u16,
+    /// Index in the list passed by caller, occurrences of recurring
+    /// event share it while `id` is assigned again on expansion
+    pub source_id: u16,
+    pub uid: Option<String>,
     pub starts_at: u16,
     pub duration: u16,
     pub title: Option<String>,
     pub location: Option<String>,
+    pub resource: Option<String>,
+    pub category: Option<String>,
+    pub date: Option<Date>,
+    /// Original start and end when times are snapped to grid
+    pub label_time: Option<(u16, u16)>,
+    pub status: Status,
+    pub private: bool,
+    pub extra: BTreeMap<String, Value>,
+    pub recurrence: Option<Rule>,
+    pub excluded_dates: Vec<Date>,
 }
 impl Event {
+    /// Identity of the event between two versions of the schedule,
+    /// events without UID are matched by position in the list. Prefixes
+    /// keep UID like "3" apart from the index of another event.
+    pub fn key(&self) -> String {
+        match self.uid {
+            Some(ref uid) => format!("uid:{}", uid),
+            None => format!("idx:{}", self.source_id),
+        }
+    }
+
     pub fn ends_at(&self) -> u16 {
         self.starts_at + self.duration
     }
@@ -35,68 +356,413 @@ impl Event {
     pub fn overlap(&self, event: &Event) -> bool {
         self.starts_at < event.ends_at() && event.starts_at < self.ends_at()
     }
+
+    /// Start and end shown to the user
+    pub fn label_range(&self) -> (u16, u16) {
+        self.label_time.unwrap_or((self.starts_at, self.ends_at()))
+    }
+
+    /// End of the event on the grid, short events are stretched to `min_duration`
+    pub fn visual_ends_at(&self, min_duration: u16) -> u16 {
+        self.starts_at + cmp::max(self.duration, min_duration)
+    }
+
+    /// Room or person the event belongs to
+    pub fn resource_name(&self) -> Option<&String> {
+        self.resource.as_ref().or(self.location.as_ref())
+    }
+
+    pub fn visual_overlap(&self, event: &Event, min_duration: u16) -> bool {
+        self.starts_at < event.visual_ends_at(min_duration)
+            && event.starts_at < self.visual_ends_at(min_duration)
+    }
+}
+
+/// Write text or attribute value with special characters escaped, runs of plain
+/// characters are written as is
+fn write_escaped<W: fmt::Write>(out: &mut W, text: &str) -> fmt::Result {
+    let mut plain_from = 0;
+    for (idx, c) in text.char_indices() {
+        let entity = match c {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' => "&quot;",
+            '\'' => "&#39;",
+            _ => continue,
+        };
+        out.write_str(&text[plain_from..idx])?;
+        out.write_str(entity)?;
+        plain_from = idx + c.len_utf8();
+    }
+    out.write_str(&text[plain_from..])
+}
+
+/// Decimals kept in numeric attributes
+pub const NUMBER_PRECISION: usize = 2;
+
+/// Number with at most `precision` decimals and without trailing zeros
+pub fn format_number(value: f64, precision: usize) -> String {
+    let mut formatted = format!("{:.*}", precision, value);
+    if formatted.contains('.') {
+        let len = formatted.trim_end_matches('0').trim_end_matches('.').len();
+        formatted.truncate(len);
+    }
+    if formatted == "-0" {
+        formatted = "0".to_string();
+    }
+    formatted
+}
+
+/// Builder methods for numeric SVG attributes
+macro_rules! number_attrs {
+    ($($method:ident => $attr:expr),*) => {
+        $(
+            pub fn $method<T: Into<f64>>(self, value: T) -> Self {
+                self.num($attr, value)
+            }
+        )*
+    };
+}
+
+/// Builder methods for SVG attributes with text values
+macro_rules! text_attrs {
+    ($($method:ident => $attr:expr),*) => {
+        $(
+            pub fn $method(self, value: &str) -> Self {
+                self.attr($attr, value)
+            }
+        )*
+    };
+}
+
+/// Child of element, text is escaped on output
+pub enum Node<'a> {
+    Element(HTMLElement<'a>),
+    Text(String),
+}
+impl<'a> From<HTMLElement<'a>> for Node<'a> {
+    fn from(el: HTMLElement<'a>) -> Node<'a> {
+        Node::Element(el)
+    }
+}
+impl<'a> From<String> for Node<'a> {
+    fn from(text: String) -> Node<'a> {
+        Node::Text(text)
+    }
+}
+impl<'a, 'b> From<&'b str> for Node<'a> {
+    fn from(text: &'b str) -> Node<'a> {
+        Node::Text(text.to_string())
+    }
+}
+impl<'a> Node<'a> {
+    pub fn visit<V: Visitor>(&self, visitor: &mut V) -> Result<(), V::Error> {
+        match self {
+            Node::Element(el) => el.visit(visitor),
+            Node::Text(text) => visitor.text(text),
+        }
+    }
+}
+impl<'a> Display for Node<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.visit(&mut Markup::new(f, None))
+    }
+}
+
+/// Walker over element tree in document order,
+/// string and DOM outputs are built by implementations of it
+pub trait Visitor {
+    type Error;
+    /// Start of element with its attributes including `data-key`
+    fn open(&mut self, tag: &str, attrs: &[(&str, &str)]) -> Result<(), Self::Error>;
+    /// Unescaped text
+    fn text(&mut self, text: &str) -> Result<(), Self::Error>;
+    fn close(&mut self, tag: &str) -> Result<(), Self::Error>;
+}
+
+/// HTML elements which never have content, so they have no closing tag
+const HTML_VOID_ELEMENTS: [&'static str; 4] = ["br", "hr", "img", "input"];
+
+/// SVG elements without children in rendered markup, HTML parser
+/// accepts `/>` on them inside of `<svg>` just like XML does
+const SVG_SHAPES: [&'static str; 9] = [
+    "circle", "ellipse", "image", "line", "path", "polygon", "polyline", "rect", "stop",
+];
+
+/// Whether empty element is written as `<tag/>`, other empty elements
+/// keep closing tag since HTML parser ignores `/>` on other HTML elements
+fn is_self_closing(tag: &str) -> bool {
+    HTML_VOID_ELEMENTS.contains(&tag) || SVG_SHAPES.contains(&tag)
+}
+
+/// Writes escaped markup of visited elements in one pass,
+/// with `indent` each element starts on its own line
+struct Markup<'i, W: fmt::Write> {
+    out: W,
+    indent: Option<&'i str>,
+    /// Whether each open element has element children
+    nested: Vec<bool>,
+    /// Open tag of the last element is not finished until it gets children
+    open_tag: bool,
+}
+impl<'i, W: fmt::Write> Markup<'i, W> {
+    fn new(out: W, indent: Option<&'i str>) -> Markup<'i, W> {
+        Markup {
+            out: out,
+            indent: indent,
+            nested: vec![],
+            open_tag: false,
+        }
+    }
+
+    fn finish_open_tag(&mut self) -> fmt::Result {
+        if self.open_tag {
+            self.open_tag = false;
+            self.out.write_char('>')?;
+        }
+        Ok(())
+    }
+
+    fn new_line(&mut self) -> fmt::Result {
+        if let Some(indent) = self.indent {
+            self.out.write_char('\n')?;
+            for _ in 0..self.nested.len() {
+                self.out.write_str(indent)?;
+            }
+        }
+        Ok(())
+    }
+}
+impl<'i, W: fmt::Write> Visitor for Markup<'i, W> {
+    type Error = fmt::Error;
+
+    fn open(&mut self, tag: &str, attrs: &[(&str, &str)]) -> fmt::Result {
+        self.finish_open_tag()?;
+        if let Some(nested) = self.nested.last_mut() {
+            *nested = true;
+        }
+        if !self.nested.is_empty() {
+            self.new_line()?;
+        }
+
+        write!(self.out, "<{}", tag)?;
+        for (attr, value) in attrs {
+            write!(self.out, " {}=\"", attr)?;
+            write_escaped(&mut self.out, value)?;
+            self.out.write_char('"')?;
+        }
+        self.nested.push(false);
+        self.open_tag = true;
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str) -> fmt::Result {
+        self.finish_open_tag()?;
+        write_escaped(&mut self.out, text)
+    }
+
+    fn close(&mut self, tag: &str) -> fmt::Result {
+        let nested = self.nested.pop() == Some(true);
+        if self.open_tag && is_self_closing(tag) {
+            self.open_tag = false;
+            return self.out.write_str("/>");
+        }
+        self.finish_open_tag()?;
+        if nested {
+            self.new_line()?;
+        }
+        write!(self.out, "</{}>", tag)
+    }
 }
 
-pub type HTMLAttr<'a> = (&'a str, String);
+/// Passes written markup to `io::Write` keeping its error
+struct IoWriter<'w, W: io::Write + 'w> {
+    out: &'w mut W,
+    error: Option<io::Error>,
+}
+impl<'w, W: io::Write> fmt::Write for IoWriter<'w, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
 
 pub struct HTMLElement<'a> {
     tag: &'a str,
-    attrs: BTreeMap<&'a str, String>,
-    body: Vec<Box<Display + 'a>>,
+    /// Identity of element between renders, rendered as `data-key`
+    key: Option<String>,
+    attrs: BTreeMap<String, String>,
+    body: Vec<Node<'a>>,
 }
 impl<'a> HTMLElement<'a> {
-    pub fn new(tag: &'a str, attrs: &[HTMLAttr<'a>]) -> HTMLElement<'a> {
-        let mut el = HTMLElement {
+    pub fn new(tag: &'a str) -> HTMLElement<'a> {
+        HTMLElement {
             tag: tag,
+            key: None,
             attrs: BTreeMap::new(),
             body: vec![],
-        };
-        el.attrs(attrs);
-        el
+        }
     }
 
-    pub fn attrs(&mut self, attrs: &[HTMLAttr<'a>]) {
-        for (attr, value) in attrs {
-            self.attrs.insert(attr, value.to_owned());
+    fn attr<T: ToString>(mut self, name: &str, value: T) -> Self {
+        self.attrs.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Numeric attribute rounded to `NUMBER_PRECISION`
+    fn num<T: Into<f64>>(self, name: &str, value: T) -> Self {
+        let value = format_number(value.into(), NUMBER_PRECISION);
+        self.attr(name, value)
+    }
+
+    number_attrs! {
+        x => "x", y => "y", x1 => "x1", y1 => "y1", x2 => "x2", y2 => "y2",
+        width => "width", height => "height",
+        r => "r", stroke_width => "stroke-width", opacity => "opacity", tabindex => "tabindex"
+    }
+
+    text_attrs! {
+        xmlns => "xmlns", id => "id", class => "class", style => "style",
+        role => "role", aria_label => "aria-label", clip_path => "clip-path",
+        fill => "fill", stroke => "stroke", stroke_dasharray => "stroke-dasharray",
+        text_anchor => "text-anchor", text_decoration => "text-decoration",
+        dx => "dx", dy => "dy", pattern_units => "patternUnits",
+        pattern_transform => "patternTransform", pointer_events => "pointer-events"
+    }
+
+    pub fn translate<X: Into<f64>, Y: Into<f64>>(self, x: X, y: Y) -> Self {
+        let transform = format!(
+            "translate({},{})",
+            format_number(x.into(), NUMBER_PRECISION),
+            format_number(y.into(), NUMBER_PRECISION)
+        );
+        self.attr("transform", transform)
+    }
+
+    pub fn view_box<W: Into<f64>, H: Into<f64>>(self, width: W, height: H) -> Self {
+        let view_box = format!(
+            "0 0 {} {}",
+            format_number(width.into(), NUMBER_PRECISION),
+            format_number(height.into(), NUMBER_PRECISION)
+        );
+        self.attr("viewBox", view_box)
+    }
+
+    /// Add `data-*` attribute, characters not allowed in its name are replaced,
+    /// `data-key` is reserved for keyed output and taken names are skipped
+    pub fn data_attr(&mut self, name: &str, value: String) {
+        let attr = format!("data-{}", sanitize_name(name));
+        if attr != "data-key" && !self.attrs.contains_key(&attr) {
+            self.attrs.insert(attr, value);
         }
     }
 
-    pub fn append_child<T: 'a>(&mut self, el: T)
+    pub fn set_key(&mut self, key: String) {
+        self.key = Some(key);
+    }
+
+    pub fn key(&self) -> Option<&String> {
+        self.key.as_ref()
+    }
+
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attrs
+    }
+
+    pub fn children(&self) -> &Vec<Node<'a>> {
+        &self.body
+    }
+
+    pub fn append_child<T>(&mut self, el: T)
     where
-        T: Display,
+        T: Into<Node<'a>>,
     {
-        self.body.push(Box::new(el));
+        self.body.push(el.into());
     }
-}
-impl<'a> Display for HTMLElement<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Create open tag
-        let mut res = format!("<{}", self.tag);
-        // Add attrs
-        for (attr, value) in &self.attrs {
-            res = format!("{} {}=\"{}\"", res, attr, value);
+
+    /// Walk the element and its children, `data-key` goes first
+    pub fn visit<V: Visitor>(&self, visitor: &mut V) -> Result<(), V::Error> {
+        let mut attrs: Vec<(&str, &str)> = vec![];
+        if let Some(ref key) = self.key {
+            attrs.push(("data-key", key));
         }
-        // Close open tag
-        res.push_str(">");
-        // Add content
-        for el in &self.body {
-            res.push_str(&el.to_string());
+        attrs.extend(
+            self.attrs
+                .iter()
+                .map(|(attr, value)| (&attr[..], &value[..])),
+        );
+
+        visitor.open(self.tag, &attrs)?;
+        for node in &self.body {
+            node.visit(visitor)?;
         }
-        // Close tag
-        res = format!("{}</{}>", res, self.tag);
+        visitor.close(self.tag)
+    }
 
-        write!(f, "{}", res)
+    /// Markup of children without the element itself
+    pub fn inner_html(&self) -> String {
+        let mut html = String::new();
+        let mut markup = Markup::new(&mut html, None);
+        for node in &self.body {
+            node.visit(&mut markup).unwrap();
+        }
+        html
+    }
+
+    /// Write markup into any `fmt::Write`, `indent` pretty prints it
+    pub fn write_to<W: fmt::Write>(&self, out: W, indent: Option<&str>) -> fmt::Result {
+        self.visit(&mut Markup::new(out, indent))
+    }
+
+    /// Write markup into any `io::Write` as UTF-8
+    pub fn write_io<W: io::Write>(&self, out: &mut W, indent: Option<&str>) -> io::Result<()> {
+        let mut writer = IoWriter {
+            out: out,
+            error: None,
+        };
+        self.write_to(&mut writer, indent).map_err(|_| {
+            writer
+                .error
+                .take()
+                .unwrap_or_else(|| io::Error::other("formatter error"))
+        })
+    }
+
+    /// Markup with each element on its own line for debugging
+    pub fn to_pretty_string(&self) -> String {
+        let mut html = String::new();
+        self.write_to(&mut html, Some("  ")).unwrap();
+        html
+    }
+}
+impl<'a> Display for HTMLElement<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.visit(&mut Markup::new(f, None))
     }
 }
 
+/// Lowercase name with characters not allowed in names of attributes
+/// and ids replaced by '-'
+pub fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '-' | '_' | '.' => c,
+            'A'..='Z' => c.to_ascii_lowercase(),
+            _ => '-',
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_html_element_new() {
-        let attrs = &[("height", 720.to_string())];
-        let el = HTMLElement::new("svg", attrs);
+        let el = HTMLElement::new("svg").height(720);
 
         assert_eq!(el.tag, "svg");
         assert_eq!(el.body.is_empty(), true);
@@ -105,19 +771,228 @@ mod tests {
 
     #[test]
     fn test_html_element_append_child() {
-        let mut el = HTMLElement::new("svg", &[]);
-        el.append_child(HTMLElement::new("g", &[]));
+        let mut el = HTMLElement::new("svg");
+        el.append_child(HTMLElement::new("g"));
         assert_eq!(el.body.len(), 1);
     }
 
     #[test]
     fn test_html_element_to_string() {
-        let mut el = HTMLElement::new("svg", &[("width", 600.to_string())]);
-        let g = HTMLElement::new("g", &[("transform", "translate(10,0)".to_string())]);
+        let mut el = HTMLElement::new("svg").width(600);
+        let g = HTMLElement::new("g").translate(10, 0);
         el.append_child(g);
         assert_eq!(
             el.to_string(),
             "<svg width=\"600\"><g transform=\"translate(10,0)\"></g></svg>"
         );
     }
+
+    #[test]
+    fn test_html_element_void() {
+        let mut g = HTMLElement::new("g");
+        g.append_child(HTMLElement::new("line").x2(600));
+        g.append_child(HTMLElement::new("text"));
+        assert_eq!(g.to_string(), "<g><line x2=\"600\"/><text></text></g>");
+
+        let mut li = HTMLElement::new("li");
+        li.append_child(HTMLElement::new("br"));
+        li.append_child(HTMLElement::new("div"));
+        assert_eq!(li.to_string(), "<li><br/><div></div></li>");
+
+        let mut rect = HTMLElement::new("rect");
+        rect.append_child(HTMLElement::new("title"));
+        assert_eq!(rect.to_string(), "<rect><title></title></rect>");
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(193.333333, 2), "193.33");
+        assert_eq!(format_number(12.5, 0), "12");
+        assert_eq!(format_number(60.0, 2), "60");
+        assert_eq!(format_number(0.30000001, 3), "0.3");
+        assert_eq!(format_number(-0.001, 2), "0");
+        assert_eq!(format_number(-1.25, 1), "-1.2");
+    }
+
+    #[test]
+    fn test_html_element_builder() {
+        let rect = HTMLElement::new("rect")
+            .x(0)
+            .y(10.004f32)
+            .width(193.33334f32)
+            .fill("#fff")
+            .height(29.6)
+            .translate(10, 14.5);
+        assert_eq!(
+            rect.to_string(),
+            "<rect fill=\"#fff\" height=\"29.6\" transform=\"translate(10,14.5)\" \
+             width=\"193.33\" x=\"0\" y=\"10\"/>"
+        );
+    }
+
+    #[test]
+    fn test_html_element_data_attr() {
+        let mut el = HTMLElement::new("g");
+        el.data_attr("meetingUrl", "https://example.com/?a=1&b=2".to_string());
+        el.data_attr("ticket id\"", "42".to_string());
+        el.data_attr("Key", "overrides key".to_string());
+        el.data_attr("meetingURL", "overrides url".to_string());
+        assert_eq!(
+            el.to_string(),
+            "<g data-meetingurl=\"https://example.com/?a=1&amp;b=2\" data-ticket-id-=\"42\"></g>"
+        );
+    }
+
+    #[test]
+    fn test_html_element_escape_attrs() {
+        let el = HTMLElement::new("g").aria_label("\"Q&A\" <1:1>");
+        assert_eq!(
+            el.to_string(),
+            "<g aria-label=\"&quot;Q&amp;A&quot; &lt;1:1&gt;\"></g>"
+        );
+    }
+
+    #[test]
+    fn test_html_element_text() {
+        let mut el = HTMLElement::new("text");
+        el.append_child("Q&A <1:1>");
+        el.set_key("event-1".to_string());
+        assert_eq!(
+            el.to_string(),
+            "<text data-key=\"event-1\">Q&amp;A &lt;1:1&gt;</text>"
+        );
+    }
+
+    struct Tags(Vec<String>);
+    impl Visitor for Tags {
+        type Error = ();
+
+        fn open(&mut self, tag: &str, attrs: &[(&str, &str)]) -> Result<(), ()> {
+            let attrs: Vec<&str> = attrs.iter().map(|(attr, _)| *attr).collect();
+            self.0.push(format!("<{} {}>", tag, attrs.join(" ")));
+            Ok(())
+        }
+
+        fn text(&mut self, text: &str) -> Result<(), ()> {
+            self.0.push(text.to_string());
+            Ok(())
+        }
+
+        fn close(&mut self, tag: &str) -> Result<(), ()> {
+            self.0.push(format!("</{}>", tag));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_html_element_visit() {
+        let mut el = HTMLElement::new("g").fill("red");
+        el.set_key("event-1".to_string());
+        el.append_child(HTMLElement::new("rect"));
+        el.append_child("Q&A");
+
+        let mut tags = Tags(vec![]);
+        el.visit(&mut tags).unwrap();
+        assert_eq!(
+            tags.0,
+            vec!["<g data-key fill>", "<rect >", "</rect>", "Q&A", "</g>"]
+        );
+    }
+
+    fn schedule<'a>(events: usize) -> HTMLElement<'a> {
+        let mut svg = HTMLElement::new("svg").width(600);
+        let mut g = HTMLElement::new("g");
+        for idx in 0..events {
+            let mut event = HTMLElement::new("g").translate(0, idx as f64);
+            event.append_child(HTMLElement::new("rect").height(30));
+            let mut text = HTMLElement::new("text");
+            text.append_child(format!("Event \"{}\"", idx));
+            event.append_child(text);
+            g.append_child(event);
+        }
+        svg.append_child(g);
+        svg
+    }
+
+    #[test]
+    fn test_html_element_pretty() {
+        let mut el = schedule(1);
+        el.append_child("Q&A");
+
+        assert_eq!(
+            el.to_pretty_string(),
+            "<svg width=\"600\">\n  \
+               <g>\n    \
+                 <g transform=\"translate(0,0)\">\n      \
+                   <rect height=\"30\"/>\n      \
+                   <text>Event &quot;0&quot;</text>\n    \
+                 </g>\n  \
+               </g>Q&amp;A\n\
+             </svg>"
+        );
+        assert_eq!(HTMLElement::new("g").to_pretty_string(), "<g></g>");
+    }
+
+    #[test]
+    fn test_html_element_write_io() {
+        let el = schedule(2);
+        let mut bytes: Vec<u8> = vec![];
+        el.write_io(&mut bytes, None).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), el.to_string());
+
+        let mut html = String::new();
+        el.write_to(&mut html, Some("\t")).unwrap();
+        assert!(html.starts_with("<svg width=\"600\">\n\t<g>\n\t\t<g transform"));
+    }
+
+    /// Serializer used before streaming, copies markup of each element
+    /// once per ancestor
+    fn format_recursive(el: &HTMLElement) -> String {
+        let mut res = format!("<{}", el.tag);
+        for (attr, value) in &el.attrs {
+            res = format!("{} {}=\"{}\"", res, attr, value);
+        }
+        res.push('>');
+        for node in &el.body {
+            match node {
+                Node::Element(child) => res.push_str(&format_recursive(child)),
+                Node::Text(text) => res.push_str(text),
+            }
+        }
+        format!("{}</{}>", res, el.tag)
+    }
+
+    /// Best of several runs to keep noise of the machine out
+    fn fastest<F: FnMut() -> usize>(mut run: F) -> ::std::time::Duration {
+        (0..5)
+            .map(|_| {
+                let started = ::std::time::Instant::now();
+                assert!(run() > 0);
+                started.elapsed()
+            })
+            .min()
+            .unwrap()
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_html_element_10k_events() {
+        for &events in &[5_000, 10_000] {
+            let el = schedule(events);
+            let recursive = fastest(|| format_recursive(&el).len());
+            let display = fastest(|| el.to_string().len());
+            let io = fastest(|| {
+                let mut bytes = Vec::with_capacity(1 << 20);
+                el.write_io(&mut bytes, None).unwrap();
+                bytes.len()
+            });
+            println!(
+                "{} events: format! {:?}, to_string {:?}, write_io {:?}",
+                events, recursive, display, io
+            );
+            assert!(display < recursive);
+            assert!(io < recursive);
+        }
+    }
 }
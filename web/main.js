@@ -3,8 +3,51 @@ const rust = import('events_matrix/events_matrix');
 window.renderEvents = (function(document) {
   'use strict';
 
+  const SVG_NS = 'http://www.w3.org/2000/svg';
+
   let wrapper;
-  function renderEvents(events, container) {
+  let state = '';
+
+  function findByKey(key) {
+    return wrapper.querySelector(`[data-key="${CSS.escape(key)}"]`);
+  }
+
+  function parseElement(html) {
+    let doc = new DOMParser().parseFromString(
+      `<svg xmlns="${SVG_NS}">${html}</svg>`, 'image/svg+xml'
+    );
+    return document.importNode(doc.documentElement.firstElementChild, true);
+  }
+
+  function applyPatch(ops) {
+    ops.forEach(op => {
+      let el = findByKey(op.key);
+      switch (op.op) {
+        case 'create': {
+          let parent = op.parent ? findByKey(op.parent) : wrapper;
+          let before = op.before ? findByKey(op.before) : null;
+          parent.insertBefore(parseElement(op.html), before);
+          break;
+        }
+        case 'update':
+          Object.keys(op.attrs).forEach(name => el.setAttribute(name, op.attrs[name]));
+          op.removed_attrs.forEach(name => el.removeAttribute(name));
+          if (op.html !== null) { el.innerHTML = op.html; }
+          break;
+        case 'move': {
+          let parent = op.parent ? findByKey(op.parent) : wrapper;
+          let before = op.before ? findByKey(op.before) : null;
+          parent.insertBefore(el, before);
+          break;
+        }
+        case 'remove':
+          el.remove();
+          break;
+      }
+    });
+  }
+
+  function renderEvents(events, container, options) {
     if (!container) { container = document.body; }
     if (!wrapper) {
       wrapper = document.createElement('div');
@@ -12,9 +55,12 @@ window.renderEvents = (function(document) {
     }
 
     let eventsStr = JSON.stringify(events);
+    let optionsStr = JSON.stringify(options || {});
     return rust.then(m => {
-      let svg = m.render_events(eventsStr);
-      wrapper.innerHTML = svg;
+      // Only changed elements are touched, the rest of DOM is kept
+      let patch = JSON.parse(m.render_patch(state, eventsStr, optionsStr));
+      applyPatch(patch.ops);
+      state = patch.state;
     });
   }
 